@@ -0,0 +1,524 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    time::Duration,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use monitor::{
+    health_check::{Health, Healthcheck},
+    hub::Hub,
+    probe::Probe,
+};
+
+use crate::{
+    resources::{
+        cpu::{CoreError, CoresProbe, Thermal},
+        memory::{Memory, MemoryProbe},
+        network::{InterfaceType, Network, NetworkProbe, OperState},
+        uptime::{Uptime, UptimeProbe},
+    },
+    state::Hubs,
+};
+
+/// A limit on a probe's output, deciding
+/// whether the resource is healthy
+pub trait Threshold<P: Probe> {
+    fn check(&self, output: &P::Output) -> Health;
+
+    /// The health when probing failed, which
+    /// is unhealthy unless the threshold
+    /// knows the error is harmless
+    fn failed(&self, error: &P::Error) -> Health
+    where
+        P::Error: Display,
+    {
+        unhealthy(format!("failed to probe: {}", error))
+    }
+}
+
+fn unhealthy(reason: impl Into<String>) -> Health {
+    Health::Unhealthy {
+        reason: Some(reason.into()),
+    }
+}
+
+/// Unhealthy with every reason, if there are any
+fn unhealthy_if(reasons: Vec<String>) -> Health {
+    match reasons.is_empty() {
+        true => Health::Healthy,
+        false => unhealthy(reasons.join(", ")),
+    }
+}
+
+/// A duration like 1h 2m or 3m 20s
+fn human(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    let parts = [(secs / 3600, "h"), (secs / 60 % 60, "m"), (secs % 60, "s")]
+        .into_iter()
+        .filter(|(x, _)| *x > 0)
+        .map(|(x, unit)| format!("{}{}", x, unit))
+        .collect::<Vec<String>>();
+
+    match parts.is_empty() {
+        true => "0s".to_string(),
+        false => parts.join(" "),
+    }
+}
+
+#[derive(
+    PartialEq, PartialOrd,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// Unhealthy once less than a percentage
+/// of the memory is available
+pub struct MemoryAvailable {
+    pub min_percent: f64,
+}
+
+impl Threshold<MemoryProbe> for MemoryAvailable {
+    fn check(&self, memory: &Memory) -> Health {
+        if memory.total == 0 {
+            return Health::Healthy;
+        }
+
+        let percent = memory.available as f64 / memory.total as f64 * 100.0;
+
+        match percent < self.min_percent {
+            true => unhealthy(format!(
+                "only {:.1}% of memory is available, under {}%",
+                percent, self.min_percent,
+            )),
+            false => Health::Healthy,
+        }
+    }
+}
+
+#[derive(
+    PartialEq, PartialOrd,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// Unhealthy once more than a percentage
+/// of the swap is used. Without swap this
+/// is always healthy
+pub struct SwapUsed {
+    pub max_percent: f64,
+}
+
+impl Threshold<MemoryProbe> for SwapUsed {
+    fn check(&self, memory: &Memory) -> Health {
+        if memory.swap_total == 0 {
+            return Health::Healthy;
+        }
+
+        let used = memory.swap_total.saturating_sub(memory.swap_free);
+        let percent = used as f64 / memory.swap_total as f64 * 100.0;
+
+        match percent > self.max_percent {
+            true => unhealthy(format!(
+                "{:.1}% of swap is used, over {}%",
+                percent, self.max_percent,
+            )),
+            false => Health::Healthy,
+        }
+    }
+}
+
+#[derive(
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// Unhealthy once a core or package is within
+/// margin degrees celsius of its crit temp, or
+/// the kernel raised its crit alarm
+pub struct Temperature {
+    pub margin: u64,
+}
+
+impl Threshold<CoresProbe> for Temperature {
+    fn check(&self, thermal: &Thermal) -> Health {
+        let packages = thermal.packages.iter()
+            .map(|x| (format!("package {}", x.id), x.temp, x.crit, x.crit_alarm));
+
+        let cores = thermal.cores.iter()
+            .map(|x| {
+                let name = match x.package {
+                    Some(package) => format!("core {} of package {}", x.count, package),
+                    None => format!("core {}", x.count),
+                };
+
+                (name, x.temp, x.crit, x.crit_alarm)
+            });
+
+        // temps are in millidegrees
+        let celsius = |x: u64| x / 1000;
+
+        let reasons = packages.chain(cores)
+            .filter_map(|(name, temp, crit, crit_alarm)| {
+                if crit_alarm {
+                    return Some(format!(
+                        "{} hit its critical {}°C",
+                        name, celsius(crit),
+                    ));
+                }
+
+                // a crit of 0 means the chip doesn't have one
+                (crit > 0 && temp + self.margin * 1000 > crit).then(|| format!(
+                    "{} is at {}°C, within {}°C of its critical {}°C",
+                    name, celsius(temp), self.margin, celsius(crit),
+                ))
+            })
+            .collect();
+
+        unhealthy_if(reasons)
+    }
+
+    fn failed(&self, error: &CoreError) -> Health {
+        match error {
+            // nothing to overheat as
+            // far as we can tell
+            CoreError::NotFound => Health::Healthy,
+            e => unhealthy(format!("failed to probe: {}", e)),
+        }
+    }
+}
+
+#[derive(
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Unhealthy when an interface is down or
+/// missing. Without interfaces every
+/// physical interface is checked.
+pub struct LinkDown {
+    pub interfaces: Vec<String>,
+}
+
+impl Threshold<NetworkProbe> for LinkDown {
+    fn check(&self, network: &Network) -> Health {
+        let mut reasons = Vec::new();
+
+        for name in &self.interfaces {
+            let found = network.interfaces.iter().any(|x| x.name == *name)
+                || network.errors.contains_key(name);
+
+            if !found {
+                reasons.push(format!("{} is missing", name));
+            }
+        }
+
+        let checked = |name: &str| match self.interfaces.is_empty() {
+            true => None,
+            false => Some(self.interfaces.iter().any(|x| x == name)),
+        };
+
+        for x in &network.interfaces {
+            let checked = checked(&x.name)
+                .unwrap_or(x.info.kind == InterfaceType::Physical);
+
+            if !checked {
+                continue;
+            }
+
+            match x.info.operstate {
+                OperState::Down => {
+                    reasons.push(format!("{} is down", x.name));
+                }
+                OperState::LowerLayerDown => {
+                    reasons.push(format!("{} is down, its lower layer is down", x.name));
+                }
+                _ => {}
+            }
+        }
+
+        // an interface that couldn't be read has
+        // no kind either, so it's only reported
+        // if it's one of the interfaces
+        for (name, error) in &network.errors {
+            if checked(name).unwrap_or(false) {
+                reasons.push(format!("{} couldn't be read: {}", name, error));
+            }
+        }
+
+        unhealthy_if(reasons)
+    }
+}
+
+#[derive(
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// Unhealthy while the system has been up
+/// for less than min, after a reboot
+pub struct RecentReboot {
+    pub min: Duration,
+}
+
+impl Threshold<UptimeProbe> for RecentReboot {
+    fn check(&self, uptime: &Uptime) -> Health {
+        match uptime.uptime < self.min {
+            true => unhealthy(format!(
+                "rebooted {} ago, under {}",
+                human(uptime.uptime), human(self.min),
+            )),
+            false => Health::Healthy,
+        }
+    }
+}
+
+/// A [`Threshold`] over the output of
+/// a hub's probe, probed on every check
+pub struct Check<P: Probe, T> {
+    pub threshold: T,
+    pub hub: Hub<P>,
+}
+
+impl<P: Probe, T: Clone> Clone for Check<P, T> {
+    fn clone(&self) -> Self {
+        Self {
+            threshold: self.threshold.clone(),
+            hub: self.hub.clone(),
+        }
+    }
+}
+
+impl<P: Probe, T> Check<P, T> {
+    pub fn new(hub: Hub<P>, threshold: T) -> Self {
+        Self {
+            threshold,
+            hub,
+        }
+    }
+}
+
+impl<P, T> Healthcheck for Check<P, T>
+where
+    P: Probe + Send + Sync,
+    P::Output: Send,
+    P::Error: Display + Send,
+    T: Threshold<P> + Sync,
+{
+    async fn health(&self) -> Health {
+        match self.hub.probe().probe().await {
+            Ok(x) => self.threshold.check(&x),
+            Err(e) => self.threshold.failed(&e),
+        }
+    }
+}
+
+#[derive(
+    PartialEq,
+    Debug,
+    Clone,
+)]
+
+/// The thresholds to check, a missing
+/// threshold isn't checked at all
+///
+/// The link and uptime checks are off by
+/// default, since a host that just booted
+/// or has an unplugged nic is otherwise
+/// unhealthy before being configured.
+pub struct Config {
+    pub memory: Option<MemoryAvailable>,
+    pub swap: Option<SwapUsed>,
+    pub temperature: Option<Temperature>,
+    pub link: Option<LinkDown>,
+    pub uptime: Option<RecentReboot>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            memory: Some(MemoryAvailable { min_percent: 10.0 }),
+            swap: Some(SwapUsed { max_percent: 80.0 }),
+            temperature: Some(Temperature { margin: 5 }),
+            link: None,
+            uptime: None,
+        }
+    }
+}
+
+impl Config {
+    /// Uses the HEALTH_MEMORY_AVAILABLE and
+    /// HEALTH_SWAP_USED percentages, the
+    /// HEALTH_TEMP_MARGIN in celsius, the
+    /// HEALTH_INTERFACES to check and the
+    /// HEALTH_MIN_UPTIME in seconds, if set.
+    /// The last two are only checked when set,
+    /// an empty HEALTH_INTERFACES checking
+    /// every physical interface. Any of them
+    /// set to off isn't checked, and any that
+    /// can't be parsed is left at its default,
+    /// with a warning
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        fn var<T>(env: &str, default: Option<T>, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+            match std::env::var(env) {
+                Ok(x) if x.trim() == "off" => None,
+                Ok(x) => parse(x.trim()).or_else(|| {
+                    tracing::warn!("invalid {}: {:?}, using the default", env, x);
+
+                    default
+                }),
+                Err(_) => default,
+            }
+        }
+
+        Self {
+            memory: var("HEALTH_MEMORY_AVAILABLE", default.memory, |x| {
+                Some(MemoryAvailable { min_percent: x.parse().ok()? })
+            }),
+            swap: var("HEALTH_SWAP_USED", default.swap, |x| {
+                Some(SwapUsed { max_percent: x.parse().ok()? })
+            }),
+            temperature: var("HEALTH_TEMP_MARGIN", default.temperature, |x| {
+                Some(Temperature { margin: x.parse().ok()? })
+            }),
+            link: var("HEALTH_INTERFACES", default.link, |x| {
+                Some(LinkDown {
+                    interfaces: x.split(',')
+                        .map(|x| x.trim().to_string())
+                        .filter(|x| !x.is_empty())
+                        .collect(),
+                })
+            }),
+            uptime: var("HEALTH_MIN_UPTIME", default.uptime, |x| {
+                Some(RecentReboot { min: Duration::from_secs(x.parse().ok()?) })
+            }),
+        }
+    }
+}
+
+#[derive(
+    Clone,
+)]
+
+/// Every configured [`Check`], over
+/// the probes of the shared hubs
+pub struct Checks {
+    pub memory: Option<Check<MemoryProbe, MemoryAvailable>>,
+    pub swap: Option<Check<MemoryProbe, SwapUsed>>,
+    pub temperature: Option<Check<CoresProbe, Temperature>>,
+    pub link: Option<Check<NetworkProbe, LinkDown>>,
+    pub uptime: Option<Check<UptimeProbe, RecentReboot>>,
+}
+
+impl Checks {
+    pub fn new(config: Config, hubs: &Hubs) -> Self {
+        Self {
+            memory: config.memory.map(|x| Check::new(hubs.memory.clone(), x)),
+            swap: config.swap.map(|x| Check::new(hubs.memory.clone(), x)),
+            temperature: config.temperature.map(|x| Check::new(hubs.cores.clone(), x)),
+            link: config.link.map(|x| Check::new(hubs.network.clone(), x)),
+            uptime: config.uptime.map(|x| Check::new(hubs.uptime.clone(), x)),
+        }
+    }
+
+    /// Runs every check at once
+    pub async fn report(&self) -> Report {
+        async fn health(check: Option<&impl Healthcheck>) -> Option<Health> {
+            Some(check?.health().await)
+        }
+
+        let (memory, swap, temperature, link, uptime) = tokio::join!(
+            health(self.memory.as_ref()),
+            health(self.swap.as_ref()),
+            health(self.temperature.as_ref()),
+            health(self.link.as_ref()),
+            health(self.uptime.as_ref()),
+        );
+
+        let checks = [
+            ("memory", memory),
+            ("swap", swap),
+            ("temperature", temperature),
+            ("link", link),
+            ("uptime", uptime),
+        ]
+        .into_iter()
+        .filter_map(|(name, x)| Some((name.to_string(), x?.into())))
+        .collect::<BTreeMap<String, Status>>();
+
+        Report {
+            healthy: checks.values().all(|x| x.healthy),
+            checks,
+        }
+    }
+}
+
+#[derive(
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+)]
+
+/// The outcome of a single check
+pub struct Status {
+    pub reason: Option<String>,
+    pub healthy: bool,
+}
+
+impl From<Health> for Status {
+    fn from(health: Health) -> Self {
+        match health {
+            Health::Healthy => Self {
+                reason: None,
+                healthy: true,
+            },
+            Health::Unhealthy { reason } => Self {
+                healthy: false,
+                reason,
+            },
+        }
+    }
+}
+
+#[derive(
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Every check by name, healthy
+/// only if all of them are
+pub struct Report {
+    pub checks: BTreeMap<String, Status>,
+    pub healthy: bool,
+}
@@ -0,0 +1,595 @@
+use std::{
+    collections::VecDeque,
+    path::Path,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{
+    de::DeserializeOwned,
+    Deserialize,
+    Serialize,
+};
+
+use monitor::probe::Probe;
+
+use tokio::{
+    io,
+    time::MissedTickBehavior,
+};
+
+use crate::{
+    resources::{
+        cpu::{CoresProbe, Thermal, UsagePercent, UsagePercentProbe},
+        disk::{DiskRates, DiskRatesProbe},
+        filesystem::{Filesystems, FilesystemsProbe},
+        hwmon::{Sensors, SensorsProbe},
+        loadavg::{LoadAvg, LoadAvgProbe},
+        memory::{Memory, MemoryProbe},
+        network::{NetworkRates, NetworkRatesProbe},
+        pressure::{Pressure, PressureProbe},
+        Source,
+    },
+    store::{self, Store, Tier},
+};
+
+/// Milliseconds since the epoch
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|x| x.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[derive(
+    Deserialize,
+    Serialize,
+    PartialEq,
+    Debug,
+    Clone,
+)]
+
+/// A probe's output and when it was
+/// taken, in milliseconds since the epoch
+///
+/// Samples downsampled by a [`Store`] are
+/// the mean of their window, which starts
+/// at timestamp, with the max in max
+pub struct Sample<T> {
+    pub timestamp: u64,
+    pub value: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<T>,
+}
+
+impl<T> Sample<T> {
+    pub fn new(timestamp: u64, value: T) -> Self {
+        Self {
+            max: None,
+            timestamp,
+            value,
+        }
+    }
+}
+
+#[derive(
+    PartialEq, Eq,
+    Debug,
+    Clone,
+)]
+
+/// How long samples are kept, and how
+/// often resources are sampled for them
+///
+/// tiers are only used once persisted, each
+/// keeping samples for longer than the last
+/// at a coarser resolution
+pub struct Config {
+    pub retention: Duration,
+    pub resolution: Duration,
+    pub tiers: Vec<Tier>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        const MINUTE: u64 = 60;
+        const HOUR: u64 = 60 * MINUTE;
+        const DAY: u64 = 24 * HOUR;
+
+        Self {
+            retention: Duration::from_secs(HOUR),
+            resolution: Duration::from_secs(5),
+            tiers: vec![
+                Tier::new(Duration::from_secs(MINUTE), Duration::from_secs(7 * DAY)),
+                Tier::new(Duration::from_secs(HOUR), Duration::from_secs(365 * DAY)),
+            ],
+        }
+    }
+}
+
+impl Config {
+    pub fn new(retention: Duration, resolution: Duration) -> Self {
+        Self {
+            tiers: Vec::new(),
+            retention,
+            resolution,
+        }
+    }
+
+    pub fn with_tiers(mut self, tiers: Vec<Tier>) -> Self {
+        self.tiers = tiers;
+        self
+    }
+
+    /// Uses the HISTORY_RETENTION and
+    /// HISTORY_RESOLUTION env variables,
+    /// in seconds, if they are set. A
+    /// retention of 0 turns history off
+    ///
+    /// HISTORY_TIERS replaces the tiers, as
+    /// a list of resolution:retention in
+    /// seconds, e.g. 60:604800,3600:31536000
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        let secs = |env: &str| {
+            std::env::var(env).ok()
+                .and_then(|x| x.parse().ok())
+                .map(Duration::from_secs)
+        };
+
+        let tiers = std::env::var("HISTORY_TIERS").ok()
+            .and_then(|x| {
+                x.split(',')
+                    .filter(|x| !x.trim().is_empty())
+                    .map(|x| {
+                        let (resolution, retention) = x.split_once(':')?;
+
+                        Some(Tier::new(
+                            Duration::from_secs(resolution.trim().parse().ok()?),
+                            Duration::from_secs(retention.trim().parse().ok()?),
+                        ))
+                    })
+                    .collect::<Option<Vec<Tier>>>()
+            });
+
+        Self {
+            retention: secs("HISTORY_RETENTION").unwrap_or(default.retention),
+            resolution: secs("HISTORY_RESOLUTION").unwrap_or(default.resolution),
+            tiers: tiers.unwrap_or(default.tiers),
+        }
+    }
+
+    /// Every tier a [`Store`] keeps, starting
+    /// with the one matching the memory
+    pub fn store_tiers(&self) -> Vec<Tier> {
+        std::iter::once(Tier::new(self.resolution, self.retention))
+            .chain(self.tiers.iter().copied())
+            .collect()
+    }
+
+    /// The most samples a [`History`] holds
+    pub fn capacity(&self) -> usize {
+        match self.resolution.as_millis() {
+            0 => 0,
+            x => (self.retention.as_millis() / x) as usize,
+        }
+    }
+}
+
+#[derive(
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// The samples to return from a [`History`]
+///
+/// since and until are inclusive, in milliseconds
+/// since the epoch. step thins the samples out to
+/// the first in every step milliseconds.
+pub struct Query {
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub step: Option<u64>,
+}
+
+/// A bounded, in memory history of a probe's
+/// outputs. Once full, or once a sample is
+/// older than the retention, the oldest
+/// samples are dropped.
+///
+/// With a [`Store`], samples are persisted
+/// as well, and queries reaching past the
+/// memory are served from its coarser tiers.
+pub struct History<T> {
+    samples: Arc<Mutex<VecDeque<Sample<T>>>>,
+    store: Option<Arc<Store<T>>>,
+    writer: Option<Arc<Writer<T>>>,
+    pub config: Config,
+}
+
+impl<T> Clone for History<T> {
+    fn clone(&self) -> Self {
+        Self {
+            samples: self.samples.clone(),
+            config: self.config.clone(),
+            writer: self.writer.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+// How many samples can be waiting on the
+// disk before new ones are only kept in
+// memory
+const BACKLOG: usize = 64;
+
+/// Appends samples to a [`Store`] from a
+/// thread of its own, in the order they
+/// were pushed, so pushing never blocks
+/// on the disk. Once closed or dropped,
+/// it waits for the backlog to be written
+/// and flushes the store.
+struct Writer<T> {
+    sender: Mutex<Option<mpsc::SyncSender<Sample<T>>>>,
+    thread: Mutex<Option<thread::JoinHandle<()>>>,
+}
+
+impl<T: Serialize + Send + 'static> Writer<T> {
+    fn new(store: Arc<Store<T>>) -> io::Result<Self> {
+        let (tx, rx) = mpsc::sync_channel::<Sample<T>>(BACKLOG);
+
+        let thread = thread::Builder::new()
+            .name("history".to_string())
+            .spawn(move || {
+                for x in rx {
+                    if let Err(e) = store.append(&x) {
+                        tracing::warn!("failed to persist a sample: {}", e);
+                    }
+                }
+
+                if let Err(e) = store.flush() {
+                    tracing::warn!("failed to persist the last window of samples: {}", e);
+                }
+            })?;
+
+        Ok(Self {
+            sender: Mutex::new(Some(tx)),
+            thread: Mutex::new(Some(thread)),
+        })
+    }
+
+    fn send(&self, sample: Sample<T>) {
+        let sender = self.sender.lock()
+            .unwrap_or_else(|x| x.into_inner());
+
+        let Some(sender) = &*sender else {
+            return;
+        };
+
+        if sender.try_send(sample).is_err() {
+            tracing::warn!("the history store is falling behind, dropped a sample");
+        }
+    }
+}
+
+impl<T> Writer<T> {
+    /// Stops taking samples, and blocks until
+    /// the backlog is written and flushed
+    fn close(&self) {
+        // closing the channel ends the thread
+        // once it has written the backlog
+        self.sender.lock()
+            .unwrap_or_else(|x| x.into_inner())
+            .take();
+
+        let thread = self.thread.lock()
+            .unwrap_or_else(|x| x.into_inner())
+            .take();
+
+        if let Some(x) = thread {
+            let _ = x.join();
+        }
+    }
+}
+
+impl<T> Drop for Writer<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self::new(Config::default())
+    }
+}
+
+impl<T> History<T> {
+    pub fn new(config: Config) -> Self {
+        Self {
+            samples: Arc::new(Mutex::new(VecDeque::new())),
+            writer: None,
+            store: None,
+            config,
+        }
+    }
+
+    /// Writes out every sample waiting on the
+    /// store, including the windows its tiers
+    /// are still aggregating, and stops
+    /// persisting new ones. Blocks on the disk.
+    pub fn close(&self) {
+        if let Some(x) = &self.writer {
+            x.close();
+        }
+    }
+
+    fn remember(&self, sample: Sample<T>) {
+        let mut samples = self.samples.lock()
+            .unwrap_or_else(|x| x.into_inner());
+
+        let oldest = sample.timestamp
+            .saturating_sub(self.config.retention.as_millis() as u64);
+
+        samples.push_back(sample);
+
+        while samples.len() > self.config.capacity()
+            || samples.front().is_some_and(|x| x.timestamp < oldest)
+        {
+            samples.pop_front();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.lock()
+            .unwrap_or_else(|x| x.into_inner())
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Serialize + Clone + Send + 'static> History<T> {
+    /// Keeps a sample, persisting it too in the
+    /// background if there's a store. A failed
+    /// write only loses the sample on disk.
+    pub fn push(&self, sample: Sample<T>) {
+        if let Some(writer) = &self.writer {
+            writer.send(sample.clone());
+        }
+
+        self.remember(sample);
+    }
+}
+
+impl<T: Serialize + DeserializeOwned + Send + 'static> History<T> {
+    /// A history persisted in dir, starting out
+    /// with the samples it has kept in memory
+    pub fn open(config: Config, dir: impl AsRef<Path>) -> Result<Self, store::Error> {
+        let store = Store::open(dir, &config.store_tiers())?;
+
+        let since = now()
+            .saturating_sub(config.retention.as_millis() as u64);
+
+        let mut history = Self::new(config);
+
+        for x in store.read(0, since, u64::MAX)? {
+            history.remember(x);
+        }
+
+        let store = Arc::new(store);
+
+        history.writer = Some(Arc::new(Writer::new(store.clone())?));
+        history.store = Some(store);
+
+        Ok(history)
+    }
+}
+
+impl<T> History<T>
+where
+    T: DeserializeOwned + Clone + Send + 'static,
+{
+    /// The samples matching a [`Query`], oldest first
+    ///
+    /// Samples older than the memory come from the
+    /// store, each tier filling in what's before
+    /// the finer one it follows.
+    pub async fn query(&self, query: &Query) -> Result<Vec<Sample<T>>, store::Error> {
+        let since = query.since.unwrap_or(0);
+        let until = query.until.unwrap_or(u64::MAX);
+
+        let (oldest, recent) = {
+            let samples = self.samples.lock()
+                .unwrap_or_else(|x| x.into_inner());
+
+            let recent = samples.iter()
+                .filter(|x| x.timestamp >= since && x.timestamp <= until)
+                .cloned()
+                .collect::<Vec<Sample<T>>>();
+
+            (samples.front().map(|x| x.timestamp), recent)
+        };
+
+        let Some(store) = self.store.clone() else {
+            return Ok(thin(recent, query.step));
+        };
+
+        let until = match oldest {
+            Some(x) => until.min(x.saturating_sub(1)),
+            None => until,
+        };
+
+        // the store blocks on its files
+        let mut samples = tokio::task::spawn_blocking(move || {
+            let mut samples = Vec::new();
+            let mut until = Some(until);
+
+            for tier in 1..store.tiers().len() {
+                let Some(before) = until.filter(|x| *x >= since) else {
+                    break;
+                };
+
+                let mut older = store.read(tier, since, before)?;
+
+                if let Some(x) = older.first() {
+                    until = x.timestamp.checked_sub(1);
+                }
+
+                older.append(&mut samples);
+                samples = older;
+            }
+
+            Ok::<_, store::Error>(samples)
+        })
+        .await
+        .map_err(io::Error::other)??;
+
+        samples.extend(recent);
+
+        Ok(thin(samples, query.step))
+    }
+}
+
+/// Keeps the first sample in every step
+/// milliseconds, of samples oldest first
+fn thin<T>(samples: Vec<Sample<T>>, step: Option<u64>) -> Vec<Sample<T>> {
+    let step = step.unwrap_or(1).max(1);
+
+    let mut out = Vec::<Sample<T>>::new();
+
+    for x in samples {
+        // windows are aligned to the epoch, so
+        // jitter in when samples were taken
+        // doesn't shift which ones are kept
+        if out.last().is_some_and(|last| last.timestamp / step == x.timestamp / step) {
+            continue;
+        }
+
+        out.push(x);
+    }
+
+    out
+}
+
+impl<T: Serialize + Clone + Send + 'static> History<T> {
+    /// Probes every resolution in the background,
+    /// keeping every successful sample. Failed
+    /// probes leave a gap.
+    ///
+    /// The probe is the history's own, so rates
+    /// are over the resolution rather than since
+    /// whoever else probed last.
+    pub fn record<P>(&self, probe: P)
+    where
+        P: Probe<Output = T> + Send + Sync + 'static,
+    {
+        let history = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(history.config.resolution);
+
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+            loop {
+                interval.tick().await;
+
+                if let Ok(x) = probe.probe().await {
+                    history.push(Sample::new(now(), x));
+                }
+            }
+        });
+    }
+}
+
+#[derive(
+    Default,
+    Clone,
+)]
+
+/// One [`History`] per resource, named
+/// like the resource's route
+pub struct Histories {
+    pub filesystems: History<Filesystems>,
+    pub network: History<NetworkRates>,
+    pub pressure: History<Pressure>,
+    pub sensors: History<Sensors>,
+    pub thermal: History<Thermal>,
+    pub disks: History<DiskRates>,
+    pub load: History<LoadAvg>,
+    pub mem: History<Memory>,
+    pub cpu: History<UsagePercent>,
+}
+
+impl Histories {
+    pub fn new(config: Config) -> Self {
+        Self {
+            filesystems: History::new(config.clone()),
+            network: History::new(config.clone()),
+            pressure: History::new(config.clone()),
+            sensors: History::new(config.clone()),
+            thermal: History::new(config.clone()),
+            disks: History::new(config.clone()),
+            load: History::new(config.clone()),
+            mem: History::new(config.clone()),
+            cpu: History::new(config),
+        }
+    }
+
+    /// Histories persisted under dir,
+    /// one directory per resource
+    pub fn open(config: Config, dir: impl AsRef<Path>) -> Result<Self, store::Error> {
+        let dir = dir.as_ref();
+
+        Ok(Self {
+            filesystems: History::open(config.clone(), dir.join("filesystems"))?,
+            network: History::open(config.clone(), dir.join("network"))?,
+            pressure: History::open(config.clone(), dir.join("pressure"))?,
+            sensors: History::open(config.clone(), dir.join("sensors"))?,
+            thermal: History::open(config.clone(), dir.join("thermal"))?,
+            disks: History::open(config.clone(), dir.join("disks"))?,
+            load: History::open(config.clone(), dir.join("load"))?,
+            mem: History::open(config.clone(), dir.join("mem"))?,
+            cpu: History::open(config, dir.join("cpu"))?,
+        })
+    }
+
+    /// Closes every [`History`], see
+    /// [`History::close`]
+    pub fn close(&self) {
+        self.filesystems.close();
+        self.network.close();
+        self.pressure.close();
+        self.sensors.close();
+        self.thermal.close();
+        self.disks.close();
+        self.load.close();
+        self.mem.close();
+        self.cpu.close();
+    }
+
+    /// Starts recording every resource, each
+    /// with a probe of its own from source
+    pub fn record(&self, source: &Source) {
+        if self.cpu.config.capacity() == 0 {
+            return;
+        }
+
+        self.filesystems.record(FilesystemsProbe::from(source));
+        self.network.record(NetworkRatesProbe::from(source));
+        self.pressure.record(PressureProbe::from(source));
+        self.sensors.record(SensorsProbe::from(source));
+        self.thermal.record(CoresProbe::from(source));
+        self.disks.record(DiskRatesProbe::from(source));
+        self.load.record(LoadAvgProbe::from(source));
+        self.mem.record(MemoryProbe::from(source));
+        self.cpu.record(UsagePercentProbe::from(source));
+    }
+}
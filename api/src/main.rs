@@ -10,6 +10,11 @@ pub mod resources;
 pub mod routes;
 pub mod tests;
 pub mod error;
+pub mod health;
+pub mod history;
+pub mod metrics;
+pub mod state;
+pub mod store;
 
 // for simple empty results
 type Any = anyhow::Result<()>;
@@ -25,35 +30,113 @@ async fn main() -> Any {
         format!("0.0.0.0:{}", PORT)
     );
 
+    let source = resources::Source::from_env();
+
+    let hubs = state::Hubs::from(&source);
+
+    let config = history::Config::from_env();
+
+    // history is only persisted, and
+    // kept across restarts, given a dir
+    let history = match std::env::var("HISTORY_DIR") {
+        Ok(dir) => history::Histories::open(config, &dir)
+            .with_context(|| format!("opening history in {}", dir))?,
+        Err(_) => history::Histories::new(config),
+    };
+
+    history.record(&source);
+
+    let health = health::Checks::new(
+        health::Config::from_env(),
+        &hubs,
+    );
+
     let socket = TcpListener::bind(&addr).await
         .with_context(|| "connecting to socket")?;
 
     let router = Router::new()
         .route("/", routing::get(routes::root))
+        .route("/metrics", routing::get(routes::metrics))
+        .route("/health", routing::get(routes::health))
+        .route("/history/:resource", routing::get(routes::history))
         .route("/mem", routing::get(routes::mem))
         .route("/cpu", routing::get(routes::cpu))
         .route("/uptime", routing::get(routes::uptime))
+        .route("/load", routing::get(routes::load))
+        .route("/pressure", routing::get(routes::pressure))
+        .route("/disks", routing::get(routes::disks))
+        .route("/filesystems", routing::get(routes::filesystems))
+        .route("/processes", routing::get(routes::processes))
+        .route("/processes/top", routing::get(routes::processes_top))
+        .route("/processes/tree", routing::get(routes::processes_tree))
         .route("/mem/rt", routing::get(routes::mem_sse))
         .route("/cpu/rt", routing::get(routes::cpu_sse))
         .route("/network", routing::get(routes::network))
+        .route("/sensors", routing::get(routes::sensors))
+        .route("/network/sockets", routing::get(routes::network_sockets))
         .route("/cpu/info", routing::get(routes::cpu_info))
         .route("/cpu/cores", routing::get(routes::cpu_cores))
+        .route("/cpu/thermal", routing::get(routes::cpu_thermal))
         .route("/cpu/usage", routing::get(routes::cpu_usage))
         .route("/uptime/rt", routing::get(routes::uptime_sse))
+        .route("/load/rt", routing::get(routes::load_sse))
+        .route("/pressure/rt", routing::get(routes::pressure_sse))
         .route("/network/rt", routing::get(routes::network_sse))
+        .route("/network/events", routing::get(routes::network_events_sse))
+        .route("/network/sockets/rt", routing::get(routes::network_sockets_sse))
+        .route("/sensors/rt", routing::get(routes::sensors_sse))
+        .route("/disks/rt", routing::get(routes::disks_sse))
+        .route("/filesystems/rt", routing::get(routes::filesystems_sse))
+        .route("/processes/top/rt", routing::get(routes::processes_top_sse))
+        .route("/processes/tree/rt", routing::get(routes::processes_tree_sse))
         .route("/cpu/info/rt", routing::get(routes::cpu_info_sse))
         .route("/cpu/cores/rt", routing::get(routes::cpu_cores_sse))
-        .route("/cpu/usage/rt", routing::get(routes::cpu_usage_sse));
+        .route("/cpu/thermal/rt", routing::get(routes::cpu_thermal_sse))
+        .route("/cpu/usage/rt", routing::get(routes::cpu_usage_sse))
+        .route("/cpu/usage/percent", routing::get(routes::cpu_usage_percent))
+        .route("/cpu/usage/percent/rt", routing::get(routes::cpu_usage_percent_sse))
+        .route("/cpu/usage/cores", routing::get(routes::cpu_usage_cores))
+        .route("/cpu/usage/cores/rt", routing::get(routes::cpu_usage_cores_sse))
+        .route("/cpu/usage/cores/percent", routing::get(routes::cpu_usage_cores_percent))
+        .route("/cpu/usage/cores/percent/rt", routing::get(routes::cpu_usage_cores_percent_sse))
+        .with_state(state::AppState {
+            history: history.clone(),
+            health,
+            hubs,
+        });
 
     tracing::info!(
         "now serving on {}", addr,
     );
 
-    axum::serve(
-        socket,
-        router,
-    ).await
-    .with_context(|| "serving")?;
+    // the sse routes never end on their own, so
+    // rather than waiting on every connection
+    // to close, shutting down stops serving
+    // once the history is on disk
+    tokio::select! {
+        res = axum::serve(socket, router) => {
+            res.with_context(|| "serving")?;
+        }
+        res = shutdown() => {
+            res.with_context(|| "waiting on signals")?;
+
+            tracing::info!("shutting down");
+        }
+    }
+
+    tokio::task::spawn_blocking(move || history.close()).await?;
 
     Ok(())
 }
+
+/// Resolves on SIGINT or SIGTERM
+async fn shutdown() -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+
+    tokio::select! {
+        res = tokio::signal::ctrl_c() => res,
+        _ = terminate.recv() => Ok(()),
+    }
+}
@@ -0,0 +1,573 @@
+use std::fmt::Write;
+
+use crate::resources::{
+    cpu::{Thermal, Usage},
+    disk::{Disks, SECTOR_SIZE},
+    filesystem::Filesystems,
+    hwmon::{ChannelKind, Sensors},
+    loadavg::LoadAvg,
+    memory::Memory,
+    network::Network,
+    pressure::{Pressure, Resource},
+    process,
+    socket::Sockets,
+    uptime::Uptime,
+};
+
+/// Every metric is prefixed with this
+pub const NAMESPACE: &str = "monitor";
+
+#[derive(
+    PartialEq, Eq,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+pub enum Kind {
+    Counter,
+    Gauge,
+}
+
+#[derive(
+    Debug,
+    Clone,
+)]
+
+struct Family {
+    samples: Vec<(String, f64)>,
+    help: &'static str,
+    name: String,
+    kind: Kind,
+}
+
+#[derive(
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Metrics in the Prometheus text exposition
+/// format, grouped by name so each family gets
+/// a single HELP and TYPE line
+pub struct Exposition {
+    families: Vec<Family>,
+}
+
+impl Exposition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn counter(
+        &mut self,
+        name: &str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) {
+        self.push(Kind::Counter, name, help, labels, value);
+    }
+
+    pub fn gauge(
+        &mut self,
+        name: &str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) {
+        self.push(Kind::Gauge, name, help, labels, value);
+    }
+
+    fn push(
+        &mut self,
+        kind: Kind,
+        name: &str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) {
+        let name = format!("{NAMESPACE}_{name}");
+
+        let labels = labels.iter()
+            .map(|(k, v)| format!("{k}=\"{}\"", escape(v)))
+            .collect::<Vec<String>>()
+            .join(",");
+
+        let family = match self.families.iter_mut().find(|x| x.name == name) {
+            Some(x) => x,
+            None => {
+                self.families.push(Family {
+                    samples: Vec::new(),
+                    name,
+                    help,
+                    kind,
+                });
+
+                self.families.last_mut()
+                    .expect("a family was just pushed")
+            }
+        };
+
+        family.samples.push((labels, value));
+    }
+
+    /// Adds everything a resource exposes
+    pub fn add(&mut self, metrics: &impl Metrics) -> &mut Self {
+        metrics.metrics(self);
+
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        for family in &self.families {
+            let kind = match family.kind {
+                Kind::Counter => "counter",
+                Kind::Gauge => "gauge",
+            };
+
+            let _ = writeln!(out, "# HELP {} {}", family.name, family.help);
+            let _ = writeln!(out, "# TYPE {} {}", family.name, kind);
+
+            for (labels, value) in &family.samples {
+                let _ = match labels.is_empty() {
+                    true => writeln!(out, "{} {}", family.name, value),
+                    false => writeln!(out, "{}{{{}}} {}", family.name, labels, value),
+                };
+            }
+        }
+
+        out
+    }
+}
+
+/// Label values escape backslashes,
+/// quotes and newlines
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// A resource that can be exposed as metrics,
+/// implemented for the output of each probe
+pub trait Metrics {
+    fn metrics(&self, out: &mut Exposition);
+}
+
+impl Metrics for Usage {
+    fn metrics(&self, out: &mut Exposition) {
+        // /proc/stat counts in clock ticks
+        let ticks = process::ticks() as f64;
+
+        for (mode, value) in [
+            ("user", self.user),
+            ("nice", self.nice),
+            ("system", self.system),
+            ("idle", self.idle),
+            ("iowait", self.iowait),
+            ("irq", self.irq),
+            ("softirq", self.softirq),
+            ("steal", self.steal),
+        ] {
+            out.counter(
+                "cpu_seconds_total",
+                "Seconds the cpus spent in each mode.",
+                &[("mode", mode)],
+                value as f64 / ticks,
+            );
+        }
+
+        // guest time is already counted in user
+        // and nice, so it has its own family
+        // for the modes to add up
+        for (mode, value) in [
+            ("user", self.guest),
+            ("nice", self.guest_nice),
+        ] {
+            out.counter(
+                "cpu_guest_seconds_total",
+                "Seconds the cpus spent running guests, also counted in cpu_seconds_total.",
+                &[("mode", mode)],
+                value as f64 / ticks,
+            );
+        }
+    }
+}
+
+impl Metrics for Thermal {
+    fn metrics(&self, out: &mut Exposition) {
+        // hwmon reports millidegrees
+        let celsius = |x: u64| x as f64 / 1000.0;
+
+        for x in &self.packages {
+            let package = x.id.to_string();
+
+            out.gauge(
+                "cpu_package_temperature_celsius",
+                "Temperature of a cpu package.",
+                &[("package", &package)],
+                celsius(x.temp),
+            );
+
+            out.gauge(
+                "cpu_package_crit_alarm",
+                "Whether a cpu package hit its critical temperature.",
+                &[("package", &package)],
+                x.crit_alarm as u8 as f64,
+            );
+        }
+
+        for x in &self.cores {
+            let package = x.package.map(|x| x.to_string());
+            let core = x.count.to_string();
+
+            // cores of a chip without a package
+            // id go without the label
+            let labels = match &package {
+                Some(package) => vec![("package", package.as_str()), ("core", core.as_str())],
+                None => vec![("core", core.as_str())],
+            };
+
+            out.gauge(
+                "cpu_core_temperature_celsius",
+                "Temperature of a cpu core.",
+                &labels,
+                celsius(x.temp),
+            );
+
+            out.gauge(
+                "cpu_core_crit_temperature_celsius",
+                "Critical temperature of a cpu core.",
+                &labels,
+                celsius(x.crit),
+            );
+
+            out.gauge(
+                "cpu_core_crit_alarm",
+                "Whether a cpu core hit its critical temperature.",
+                &labels,
+                x.crit_alarm as u8 as f64,
+            );
+        }
+    }
+}
+
+impl Metrics for Sensors {
+    fn metrics(&self, out: &mut Exposition) {
+        for chip in &self.chips {
+            let hwmon = chip.path.file_name()
+                .map(|x| x.to_string_lossy())
+                .unwrap_or_default();
+
+            for x in &chip.channels {
+                // the base units, and how many
+                // of hwmon's units are in one
+                let (kind, unit, per) = match x.kind {
+                    ChannelKind::Temp => ("temp", "celsius", 1e3),
+                    ChannelKind::Fan => ("fan", "rpm", 1.0),
+                    ChannelKind::In => ("in", "volts", 1e3),
+                    ChannelKind::Power => ("power", "watts", 1e6),
+                    ChannelKind::Curr => ("curr", "amps", 1e3),
+                };
+
+                let sensor = format!("{}{}", kind, x.index);
+
+                let mut labels = vec![
+                    ("chip", hwmon.as_ref()),
+                    ("chip_name", chip.name.as_str()),
+                    ("sensor", sensor.as_str()),
+                ];
+
+                labels.extend(x.label.as_deref().map(|x| ("label", x)));
+
+                for (limit, value) in [
+                    (None, x.input),
+                    (Some("min"), x.min),
+                    (Some("max"), x.max),
+                    (Some("lcrit"), x.lcrit),
+                    (Some("crit"), x.crit),
+                    (Some("emergency"), x.emergency),
+                ] {
+                    let Some(value) = value else {
+                        continue;
+                    };
+
+                    let name = match limit {
+                        Some(limit) => format!("hwmon_{}_{}_{}", kind, limit, unit),
+                        None => format!("hwmon_{}_{}", kind, unit),
+                    };
+
+                    out.gauge(
+                        &name,
+                        "A hwmon sensor's reading or limit.",
+                        &labels,
+                        value as f64 / per,
+                    );
+                }
+
+                for alarm in &x.alarms {
+                    let mut labels = labels.clone();
+
+                    labels.push(("alarm", alarm.as_str()));
+
+                    out.gauge(
+                        "hwmon_alarm",
+                        "Hwmon alarms currently raised, by the limit they're for.",
+                        &labels,
+                        1.0,
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Metrics for Memory {
+    fn metrics(&self, out: &mut Exposition) {
+        // meminfo is in kB
+        for (name, help, value) in [
+            ("memory_total_bytes", "Total usable memory.", self.total),
+            ("memory_free_bytes", "Memory that is completely unused.", self.free),
+            ("memory_available_bytes", "Memory available without swapping.", self.available),
+            ("memory_buffers_bytes", "Memory used by block device buffers.", self.buffers),
+            ("memory_cached_bytes", "Memory used by the page cache.", self.cached),
+            ("memory_active_bytes", "Memory used recently.", self.active),
+            ("memory_inactive_bytes", "Memory not used recently.", self.inactive),
+            ("memory_dirty_bytes", "Memory waiting to be written back.", self.dirty),
+            ("memory_swap_total_bytes", "Total swap space.", self.swap_total),
+            ("memory_swap_free_bytes", "Unused swap space.", self.swap_free),
+            ("memory_swap_cached_bytes", "Swapped memory that is also in memory.", self.swap_cached),
+        ] {
+            out.gauge(name, help, &[], (value * 1024) as f64);
+        }
+    }
+}
+
+impl Metrics for Network {
+    fn metrics(&self, out: &mut Exposition) {
+        for x in &self.interfaces {
+            let labels = [("interface", x.name.as_str())];
+
+            let stats = &x.stats;
+
+            for (name, help, value) in [
+                ("network_receive_bytes_total", "Bytes received.", Some(stats.rx_bytes)),
+                ("network_transmit_bytes_total", "Bytes transmitted.", Some(stats.tx_bytes)),
+                ("network_receive_packets_total", "Packets received.", Some(stats.rx_packets)),
+                ("network_transmit_packets_total", "Packets transmitted.", Some(stats.tx_packets)),
+                ("network_receive_errors_total", "Receive errors.", stats.rx_errors),
+                ("network_transmit_errors_total", "Transmit errors.", stats.tx_errors),
+                ("network_receive_drop_total", "Received packets dropped.", stats.rx_dropped),
+                ("network_transmit_drop_total", "Transmitted packets dropped.", stats.tx_dropped),
+                ("network_receive_fifo_total", "Receive fifo errors.", stats.rx_fifo_errors),
+                ("network_transmit_fifo_total", "Transmit fifo errors.", stats.tx_fifo_errors),
+                ("network_receive_multicast_total", "Multicast packets received.", stats.multicast),
+                ("network_transmit_colls_total", "Transmit collisions.", stats.collisions),
+            ] {
+                if let Some(value) = value {
+                    out.counter(name, help, &labels, value as f64);
+                }
+            }
+
+            out.gauge(
+                "network_up",
+                "Whether the operstate of the interface is up.",
+                &labels,
+                (x.info.operstate.to_string() == "up") as u8 as f64,
+            );
+
+            // speed is in Mbit/s, 0 when unknown
+            if x.info.speed > 0 {
+                out.gauge(
+                    "network_speed_bytes",
+                    "Link speed of the interface.",
+                    &labels,
+                    x.info.speed as f64 * 1e6 / 8.0,
+                );
+            }
+
+            if let Some(mtu) = x.info.mtu {
+                out.gauge(
+                    "network_mtu_bytes",
+                    "Mtu of the interface.",
+                    &labels,
+                    mtu as f64,
+                );
+            }
+        }
+
+        for interface in self.errors.keys() {
+            out.gauge(
+                "network_interface_error",
+                "Whether the interface couldn't be read.",
+                &[("interface", interface)],
+                1.0,
+            );
+        }
+    }
+}
+
+impl Metrics for Uptime {
+    fn metrics(&self, out: &mut Exposition) {
+        out.counter(
+            "uptime_seconds_total",
+            "Seconds since boot.",
+            &[],
+            self.uptime.as_secs_f64(),
+        );
+
+        out.counter(
+            "idle_seconds_total",
+            "Seconds the cpus were idle since boot, summed over cpus.",
+            &[],
+            self.idle.as_secs_f64(),
+        );
+    }
+}
+
+impl Metrics for LoadAvg {
+    fn metrics(&self, out: &mut Exposition) {
+        out.gauge("load1", "1 minute load average.", &[], self.one);
+        out.gauge("load5", "5 minute load average.", &[], self.five);
+        out.gauge("load15", "15 minute load average.", &[], self.fifteen);
+
+        out.gauge(
+            "tasks_running",
+            "Tasks currently runnable.",
+            &[],
+            self.running as f64,
+        );
+
+        out.gauge(
+            "tasks",
+            "Tasks that exist.",
+            &[],
+            self.total as f64,
+        );
+    }
+}
+
+impl Metrics for Pressure {
+    fn metrics(&self, out: &mut Exposition) {
+        let resources = [
+            ("cpu", &self.cpu),
+            ("memory", &self.memory),
+            ("io", &self.io),
+            ("irq", &self.irq),
+        ];
+
+        for (resource, x) in resources {
+            let Some(Resource { some, full }) = x else {
+                continue;
+            };
+
+            for (kind, stall) in [("some", some), ("full", full)] {
+                let Some(stall) = stall else {
+                    continue;
+                };
+
+                let labels = [("resource", resource), ("kind", kind)];
+
+                // psi counts in microseconds
+                out.counter(
+                    "pressure_stalled_seconds_total",
+                    "Seconds tasks were stalled on a resource.",
+                    &labels,
+                    stall.total as f64 / 1e6,
+                );
+
+                out.gauge(
+                    "pressure_avg10_ratio",
+                    "Share of the last 10 seconds tasks were stalled.",
+                    &labels,
+                    stall.avg10 / 100.0,
+                );
+            }
+        }
+    }
+}
+
+impl Metrics for Disks {
+    fn metrics(&self, out: &mut Exposition) {
+        // diskstats times are in milliseconds
+        let secs = |x: u64| x as f64 / 1000.0;
+
+        for x in &self.disks {
+            let labels = [("device", x.name.as_str())];
+
+            for (name, help, value) in [
+                ("disk_reads_completed_total", "Reads completed.", x.reads as f64),
+                ("disk_writes_completed_total", "Writes completed.", x.writes as f64),
+                ("disk_read_bytes_total", "Bytes read.", (x.sectors_read * SECTOR_SIZE) as f64),
+                ("disk_written_bytes_total", "Bytes written.", (x.sectors_written * SECTOR_SIZE) as f64),
+                ("disk_read_time_seconds_total", "Seconds spent reading.", secs(x.read_time)),
+                ("disk_write_time_seconds_total", "Seconds spent writing.", secs(x.write_time)),
+                ("disk_io_time_seconds_total", "Seconds spent doing io.", secs(x.io_time)),
+            ] {
+                out.counter(name, help, &labels, value);
+            }
+
+            out.gauge(
+                "disk_io_now",
+                "Ios currently in flight.",
+                &labels,
+                x.in_flight as f64,
+            );
+        }
+    }
+}
+
+impl Metrics for Filesystems {
+    fn metrics(&self, out: &mut Exposition) {
+        for x in self.filesystems.iter().filter(|x| !x.pseudo) {
+            let Some(usage) = x.usage else {
+                continue;
+            };
+
+            let mountpoint = x.mountpoint.to_string_lossy();
+
+            let labels = [
+                ("device", x.device.as_str()),
+                ("mountpoint", &mountpoint),
+                ("fstype", x.fstype.as_str()),
+            ];
+
+            for (name, help, value) in [
+                ("filesystem_size_bytes", "Size of the filesystem.", usage.size),
+                ("filesystem_free_bytes", "Free space on the filesystem.", usage.free),
+                ("filesystem_avail_bytes", "Space available to unprivileged users.", usage.available),
+                ("filesystem_files", "Inodes on the filesystem.", usage.inodes),
+                ("filesystem_files_free", "Free inodes on the filesystem.", usage.inodes_free),
+            ] {
+                out.gauge(name, help, &labels, value as f64);
+            }
+
+            out.gauge(
+                "filesystem_readonly",
+                "Whether the filesystem is mounted read only.",
+                &labels,
+                x.read_only as u8 as f64,
+            );
+        }
+    }
+}
+
+impl Metrics for Sockets {
+    fn metrics(&self, out: &mut Exposition) {
+        for (state, count) in &self.states {
+            let state = state.to_string();
+
+            out.gauge(
+                "tcp_connections",
+                "Tcp sockets by state.",
+                &[("state", &state)],
+                *count as f64,
+            );
+        }
+
+        out.gauge("udp_sockets", "Udp sockets.", &[], self.udp as f64);
+    }
+}
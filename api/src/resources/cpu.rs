@@ -1,10 +1,12 @@
-use std::{collections::HashMap, num::{ParseFloatError, ParseIntError}, path::PathBuf};
-use futures_util::{stream, StreamExt, TryStreamExt};
+use std::{collections::{BTreeMap, HashMap}, num::{ParseFloatError, ParseIntError}, path::{Path, PathBuf}, time::Duration};
+use futures_util::TryStreamExt;
 use tokio_stream::wrappers::ReadDirStream;
 use serde::{Deserialize, Serialize};
-use monitor::probe::Probe;
+use monitor::{probe::Probe, rate::{CounterReset, Counters, RateProbe}};
 use thiserror::Error;
 
+use super::{hwmon::{self, SensorsProbe}, Source};
+
 use tokio::{
     fs::{read_dir, File},
     io::{
@@ -32,14 +34,58 @@ use tokio::{
 ///
 /// This may use kernel file system
 /// callbacks to get information
+///
+/// Sections that failed to probe are
+/// left empty, with the reason why in
+/// errors. Most VMs have no thermal
+/// sensors at all, for example.
 pub struct Cpu {
-    pub cores: Vec<Core>,
-    pub usage: Usage,
-    pub info: Info,
+    #[serde(flatten)]
+    pub thermal: Thermal,
+    pub sensor_status: Availability,
+    pub usage: Option<Usage>,
+    pub info: Option<Info>,
+    pub errors: BTreeMap<Section, String>,
     // TODO make other
     // stuff here--
 }
 
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+#[serde(rename_all = "lowercase")]
+pub enum Availability {
+    #[default]
+    Available,
+    Unavailable,
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+#[serde(rename_all = "lowercase")]
+/// A part of the composite [`Cpu`]
+pub enum Section {
+    Thermal,
+    Usage,
+    Info,
+}
+
 #[derive(
     Error,
     Debug,
@@ -69,13 +115,94 @@ pub enum CpuError {
     Eq,
 )]
 
+/// A single core, with the package it
+/// belongs to if its chip has one
 pub struct Core {
+    pub crit_alarm: bool,
+    pub package: Option<usize>,
     pub max_temp: u64,
     pub count: usize,
     pub temp: u64,
     pub crit: u64,
 }
 
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+    Ord,
+    Eq,
+)]
+
+/// The temperature of a whole cpu
+/// package (socket), labelled
+/// "Package id N" by coretemp
+pub struct Package {
+    pub crit_alarm: bool,
+    pub max_temp: u64,
+    pub temp: u64,
+    pub crit: u64,
+    pub id: usize,
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+    Ord,
+    Eq,
+)]
+
+/// Any other coretemp sensor that
+/// isn't a core or a package
+pub struct Sensor {
+    pub crit_alarm: bool,
+    pub package: Option<usize>,
+    pub max_temp: u64,
+    pub label: String,
+    pub temp: u64,
+    pub crit: u64,
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+    Ord,
+    Eq,
+)]
+
+/// Every coretemp sensor, across
+/// all of the cpu packages
+pub struct Thermal {
+    pub packages: Vec<Package>,
+    pub sensors: Vec<Sensor>,
+    pub cores: Vec<Core>,
+}
+
+impl Thermal {
+    /// Whether the kernel reports any of the
+    /// sensors as having hit its crit temp
+    pub fn crit_alarm(&self) -> bool {
+        self.packages.iter().any(|x| x.crit_alarm)
+            || self.sensors.iter().any(|x| x.crit_alarm)
+            || self.cores.iter().any(|x| x.crit_alarm)
+    }
+}
+
 #[derive(
     Error,
     Debug,
@@ -88,197 +215,310 @@ pub enum CoreError {
     ParseInt(#[from] ParseIntError),
     #[error("invalid core label: {0}")]
     InvalidLabel(String),
+    #[error(transparent)]
+    Hwmon(#[from] hwmon::Error),
+    #[error("no coretemp hwmon chip found")]
+    NotFound,
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Thermal`] sensors from
+/// the coretemp hwmon directory
+///
+/// Unless a directory is given, every
+/// coretemp chip is looked up through
+/// the hwmon class, since the hwmon
+/// index changes between boots
+pub struct CoresProbe {
+    pub sensors: SensorsProbe,
+    pub dir: Option<PathBuf>,
 }
 
-pub struct Cores;
+impl CoresProbe {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            sensors: SensorsProbe::default(),
+            dir: Some(dir.into()),
+        }
+    }
+}
 
-impl Probe for Cores {
-    type Output = Vec<Core>;
+impl From<&Source> for CoresProbe {
+    fn from(source: &Source) -> Self {
+        Self {
+            sensors: source.into(),
+            dir: source.coretemp.clone(),
+        }
+    }
+}
+
+impl Default for CoresProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for CoresProbe {
+    type Output = Thermal;
 
     type Error = CoreError;
 
-    async fn probe() -> Result<Self::Output, Self::Error> {
-        let sysfsct =
-            "/sys/devices/platform/coretemp.0/hwmon/hwmon4/";
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let dirs = match &self.dir {
+            Some(dir) => vec![dir.clone()],
+            None => self.sensors.find("coretemp")
+                .await?,
+        };
 
-        let coretemp_dir = std::env::var("CORETEMP")
-            .unwrap_or(sysfsct.into());
+        if dirs.is_empty() {
+            return Err(CoreError::NotFound);
+        }
 
-        let temps = ReadDirStream::new(
-            read_dir(coretemp_dir).await?
-        ).try_filter_map(|x| async move {
-            if !x.file_type().await?.is_file() {
-                return Ok(None::<(String, PathBuf)>);
-            }
+        let mut thermal = Thermal::default();
 
-            let fname = x.file_name();
+        for dir in &dirs {
+            let x = coretemp(dir).await?;
 
-            let fname = fname
-                .to_string_lossy();
+            thermal.packages.extend(x.packages);
+            thermal.sensors.extend(x.sensors);
+            thermal.cores.extend(x.cores);
+        }
 
-            if fname.starts_with("temp") {
-                let fname = fname
-                    .to_string();
+        Ok(thermal)
+    }
+}
 
-                let path = x.path();
+async fn coretemp(dir: &Path) -> Result<Thermal, CoreError> {
+    let temps = ReadDirStream::new(
+        read_dir(dir).await?
+    ).try_filter_map(|x| async move {
+        if !x.file_type().await?.is_file() {
+            return Ok(None::<(String, PathBuf)>);
+        }
 
-                Ok(Some((fname, path)))
-            } else {
-                Ok(None)
-            }
-        })
-        .try_collect::<HashMap<String, PathBuf>>()
-            .await?;
+        let fname = x.file_name();
 
-        #[allow(unused)]
-        struct Coretemp {
-            crit_alarm: u64,
-            label: String,
-            num: usize,
-            input: u64,
-            crit: u64,
-            max: u64,
+        let fname = fname
+            .to_string_lossy();
+
+        if fname.starts_with("temp") {
+            let fname = fname
+                .to_string();
+
+            let path = x.path();
+
+            Ok(Some((fname, path)))
+        } else {
+            Ok(None)
         }
+    })
+    .try_collect::<HashMap<String, PathBuf>>()
+        .await?;
+
+    struct Coretemp {
+        crit_alarm: bool,
+        label: String,
+        input: u64,
+        crit: u64,
+        max: u64,
+    }
 
-        let temps = tokio_stream::StreamExt::map_while(
-            stream::iter(1usize..), |x|
-        {
-            let crit_alarm = temps.get(
-                &format!("temp{x}_crit_alarm")
-            )?;
+    // the indices follow the core ids,
+    // which aren't always contiguous
+    let mut nums = temps.keys().filter_map(|x| {
+        x.strip_prefix("temp")?
+            .strip_suffix("_input")?
+            .parse::<usize>()
+            .ok()
+    })
+    .collect::<Vec<usize>>();
 
-            let input = temps.get(
-                &format!("temp{x}_input")
-            )?;
+    nums.sort();
 
-            let label = temps.get(
-                &format!("temp{x}_label")
-            )?;
+    let mut coretemps = Vec::new();
 
-            let crit = temps.get(
-                &format!("temp{x}_crit")
-            )?;
+    for x in nums {
+        let read = |attr: &str| temps.get(
+            &format!("temp{x}_{attr}")
+        ).cloned();
 
-            let max = temps.get(
-                &format!("temp{x}_max")
-            )?;
+        let Some(label) = read("label") else {
+            continue;
+        };
 
-            let a = (
-                crit_alarm,
-                input,
-                label,
-                crit,
-                max,
-                x,
-            );
+        let label = read_to(&label).await?
+            .trim()
+            .to_string();
 
-            Some(a)
-        })
-        // fix this at some point,
-        // could make way faster
-        .then(|(
+        // crit and max aren't exposed by
+        // every cpu, those default to 0
+        let input = read_u64(read("input")).await?;
+        let crit = read_u64(read("crit")).await?;
+        let max = read_u64(read("max")).await?;
+
+        let crit_alarm = read_u64(read("crit_alarm"))
+            .await? != 0;
+
+        coretemps.push(Coretemp {
             crit_alarm,
-            input,
             label,
+            input,
             crit,
             max,
-            num,
-        )| async move {
-
-            let mut buf = [0; 1024];
-
-            let n = File::open(crit_alarm).await?
-                .read(&mut buf).await?;
-
-            let crit_alarm = String::from_utf8_lossy(
-                &buf[..n]
-            ).trim().parse::<u64>()?;
-
-            let n = File::open(input).await?
-                .read(&mut buf).await?;
+        });
+    }
 
-            let input = String::from_utf8_lossy(
-                &buf[..n]
-            ).trim().parse::<u64>()?;
+    // cores belong to the package of their
+    // chip, if it reports one
+    let package = coretemps.iter().find_map(|x| {
+        x.label.strip_prefix("Package id")?
+            .trim()
+            .parse::<usize>()
+            .ok()
+    });
+
+    let mut thermal = Thermal::default();
+
+    for x in coretemps {
+        if let Some(num) = x.label.strip_prefix("Package id") {
+            thermal.packages.push(Package {
+                crit_alarm: x.crit_alarm,
+                id: num.trim().parse()?,
+                max_temp: x.max,
+                temp: x.input,
+                crit: x.crit,
+            });
 
-            let n = File::open(label).await?
-                .read(&mut buf).await?;
+            continue;
+        }
 
-            let label = String::from_utf8_lossy(
-                &buf[..n]
-            ).to_string();
+        if !x.label.starts_with("Core") {
+            thermal.sensors.push(Sensor {
+                crit_alarm: x.crit_alarm,
+                max_temp: x.max,
+                label: x.label,
+                temp: x.input,
+                crit: x.crit,
+                package,
+            });
 
-            let n = File::open(crit).await?
-                .read(&mut buf).await?;
+            continue;
+        }
 
-            let crit = String::from_utf8_lossy(
-                &buf[..n]
-            ).trim().parse::<u64>()?;
+        let num = x.label.split_at_checked(
+            5
+        ).ok_or(CoreError::InvalidLabel(
+            x.label.to_owned()
+        ))?.1;
+
+        let count = num.trim()
+            .parse::<usize>()?;
+
+        thermal.cores.push(Core {
+            crit_alarm: x.crit_alarm,
+            max_temp: x.max,
+            temp: x.input,
+            crit: x.crit,
+            package,
+            count,
+        });
+    }
 
-            let n = File::open(max).await?
-                .read(&mut buf).await?;
+    Ok(thermal)
+}
 
-            let max = String::from_utf8_lossy(
-                &buf[..n]
-            ).trim().parse::<u64>()?;
+async fn read_to(path: &Path) -> Result<String, CoreError> {
+    let mut buf = [0; 1024];
 
-            let coretemp = Coretemp {
-                crit_alarm,
-                input,
-                label,
-                crit,
-                max,
-                num,
-            };
+    let n = File::open(path).await?
+        .read(&mut buf).await?;
 
-            Ok::<Coretemp, CoreError>(
-                coretemp,
-            )
-        })
-        .try_collect::<Vec<Coretemp>>().await?;
+    Ok(String::from_utf8_lossy(&buf[..n])
+        .to_string())
+}
 
-        let cores = temps.iter().filter(|x| {
-            x.label.starts_with("Core")
-        })
-        .map(|x| {
-            let num = x.label.trim().split_at_checked(
-                5
-            ).ok_or(CoreError::InvalidLabel(
-                x.label.to_owned()
-            ))?.1;
+async fn read_u64(path: Option<PathBuf>) -> Result<u64, CoreError> {
+    let Some(path) = path else {
+        return Ok(0);
+    };
 
-            let count = num.trim()
-                .parse::<usize>()?;
+    Ok(read_to(&path).await?
+        .trim()
+        .parse::<u64>()?)
+}
 
-            let core = Core {
-                max_temp: x.max,
-                temp: x.input,
-                crit: x.crit,
-                count,
-            };
+#[derive(
+    Default,
+    Clone,
+    Debug,
+)]
 
-            Ok::<Core, CoreError>(core)
-        })
-        .try_collect()?;
+/// Probes the whole [`Cpu`] by
+/// combining the other cpu probes
+pub struct CpuProbe {
+    pub usage: UsageProbe,
+    pub cores: CoresProbe,
+    pub info: InfoProbe,
+}
 
-        Ok(cores)
+impl From<&Source> for CpuProbe {
+    fn from(source: &Source) -> Self {
+        Self {
+            usage: source.into(),
+            cores: source.into(),
+            info: source.into(),
+        }
     }
 }
 
-impl Probe for Cpu {
+impl Probe for CpuProbe {
     type Error = CpuError;
 
     type Output = Cpu;
 
-    async fn probe() -> Result<Self::Output, Self::Error> {
-        let usage = Usage::probe().await?;
-        let cores = Cores::probe().await?;
-        let info = Info::probe().await?;
+    // only fails if every section did,
+    // anything else is reported in errors
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let mut errors = BTreeMap::new();
+
+        let usage = self.usage.probe().await
+            .inspect_err(|e| {
+                errors.insert(Section::Usage, e.to_string());
+            });
+
+        let thermal = self.cores.probe().await
+            .inspect_err(|e| {
+                errors.insert(Section::Thermal, e.to_string());
+            });
+
+        let info = self.info.probe().await
+            .inspect_err(|e| {
+                errors.insert(Section::Info, e.to_string());
+            });
+
+        let (thermal, sensors) = match thermal {
+            Ok(x) => (x, Availability::Available),
+            Err(_) => (Thermal::default(), Availability::Unavailable),
+        };
+
+        if sensors == Availability::Unavailable && info.is_err() {
+            // the usage error is the most
+            // likely to explain the rest
+            if let Err(e) = usage {
+                return Err(e.into());
+            }
+        }
 
-        Ok(Self {
-            cores,
-            usage,
-            info,
+        Ok(Cpu {
+            usage: usage.ok(),
+            info: info.ok(),
+            thermal,
+            sensor_status: sensors,
+            errors,
         })
     }
 }
@@ -324,18 +564,48 @@ pub enum UsageError {
     ParseInt(ParseIntError),
     #[error("cpu is missing from stat")]
     CpuMissing,
+    #[error("cpu usage {0}")]
+    CounterReset(#[from] CounterReset),
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Usage`] from
+/// the /proc/stat file
+pub struct UsageProbe {
+    pub stat: PathBuf,
 }
 
-impl Probe for Usage {
+impl UsageProbe {
+    pub fn new(stat: impl Into<PathBuf>) -> Self {
+        Self {
+            stat: stat.into(),
+        }
+    }
+}
+
+impl From<&Source> for UsageProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.proc("STAT", "stat"))
+    }
+}
+
+impl Default for UsageProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for UsageProbe {
     type Output = Usage;
 
     type Error = UsageError;
 
-    async fn probe() -> Result<Self::Output, Self::Error> {
-        let stat_file = std::env::var("STAT")
-            .unwrap_or("/proc/stat".into());
-
-        let file = File::open(stat_file).await?;
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let file = File::open(&self.stat).await?;
 
         let mut cpu = String::new();
 
@@ -346,10 +616,16 @@ impl Probe for Usage {
             return Err(UsageError::CpuMissing);
         }
 
-        let cpu = cpu.split_off(3);
+        Usage::from_columns(&cpu[3..])
+    }
+}
 
+impl Usage {
+    /// Parses the columns of a cpu line
+    /// in /proc/stat, after its label
+    fn from_columns(columns: &str) -> Result<Self, UsageError> {
         let parts =
-            cpu.split_whitespace();
+            columns.split_whitespace();
 
         let [
             user,
@@ -384,8 +660,252 @@ impl Probe for Usage {
             irq,
         })
     }
+
+    /// All of the jiffies the cpu has spent, guest
+    /// time is already counted in user and nice
+    pub fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+
+    /// The [`UsagePercent`] between an earlier
+    /// sample and this one
+    ///
+    /// Returns None if any counter went backwards
+    /// (e.g. a cpu went offline), since there is
+    /// no meaningful delta across a reset, or if
+    /// no time passed between the samples
+    pub fn percent_since(&self, prev: &Usage) -> Option<UsagePercent> {
+        let [
+            user,
+            nice,
+            system,
+            idle,
+            iowait,
+            irq,
+            softirq,
+            steal,
+        ] = [
+            (self.user, prev.user),
+            (self.nice, prev.nice),
+            (self.system, prev.system),
+            (self.idle, prev.idle),
+            (self.iowait, prev.iowait),
+            (self.irq, prev.irq),
+            (self.softirq, prev.softirq),
+            (self.steal, prev.steal),
+        ].try_map(|(now, prev)| now.checked_sub(prev))?;
+
+        let total = user + nice + system + idle
+            + iowait + irq + softirq + steal;
+
+        if total == 0 {
+            return None;
+        }
+
+        let percent = |x: u64| {
+            x as f64 / total as f64 * 100.0
+        };
+
+        Some(UsagePercent {
+            busy: percent(total - idle - iowait),
+            softirq: percent(softirq),
+            system: percent(system),
+            iowait: percent(iowait),
+            steal: percent(steal),
+            idle: percent(idle),
+            user: percent(user),
+            nice: percent(nice),
+            irq: percent(irq),
+        })
+    }
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// How much of the time between two
+/// [`Usage`] samples the cpu spent in
+/// each state, from 0 to 100
+///
+/// busy is everything but idle and iowait
+pub struct UsagePercent {
+    pub softirq: f64,
+    pub system: f64,
+    pub iowait: f64,
+    pub steal: f64,
+    pub busy: f64,
+    pub idle: f64,
+    pub user: f64,
+    pub nice: f64,
+    pub irq: f64,
+}
+
+impl Counters for UsageProbe {
+    fn rate_since(now: &Usage, prev: &Usage, _: Duration) -> Option<UsagePercent> {
+        now.percent_since(prev)
+    }
+
+    type Rate = UsagePercent;
+}
+
+/// Probes the [`UsagePercent`] since
+/// the previous probe
+pub type UsagePercentProbe = RateProbe<UsageProbe>;
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// The [`Usage`] of a single logical
+/// cpu, from its cpuN line in /proc/stat
+pub struct CoreUsage {
+    pub usage: Usage,
+    pub cpu: usize,
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`CoreUsage`] of every
+/// logical cpu from /proc/stat
+pub struct CoresUsageProbe {
+    pub stat: PathBuf,
+}
+
+impl CoresUsageProbe {
+    pub fn new(stat: impl Into<PathBuf>) -> Self {
+        Self {
+            stat: stat.into(),
+        }
+    }
+}
+
+impl From<&Source> for CoresUsageProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.proc("STAT", "stat"))
+    }
+}
+
+impl Default for CoresUsageProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for CoresUsageProbe {
+    type Output = Vec<CoreUsage>;
+
+    type Error = UsageError;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let file = File::open(&self.stat).await?;
+
+        let mut lines = BufReader::new(file)
+            .lines();
+
+        let mut cores = Vec::new();
+
+        while let Some(line) = lines.next_line().await? {
+            // the aggregate line is just "cpu",
+            // every other cpu line is numbered
+            let Some(line) = line.strip_prefix("cpu") else {
+                continue;
+            };
+
+            let Some((cpu, columns)) = line.split_once(' ') else {
+                continue;
+            };
+
+            let Ok(cpu) = cpu.parse::<usize>() else {
+                continue;
+            };
+
+            let usage = Usage::from_columns(
+                columns
+            )?;
+
+            cores.push(CoreUsage {
+                usage,
+                cpu,
+            });
+        }
+
+        Ok(cores)
+    }
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// The [`UsagePercent`] of a single
+/// logical cpu
+pub struct CorePercent {
+    pub percent: UsagePercent,
+    pub cpu: usize,
+}
+
+impl Counters for CoresUsageProbe {
+    /// Cpus that just came online, or whose
+    /// counters were reset, are left out
+    fn rate_since(now: &Vec<CoreUsage>, prev: &Vec<CoreUsage>, _: Duration) -> Option<Vec<CorePercent>> {
+        let prev = prev.iter()
+            .map(|x| (x.cpu, x.usage))
+            .collect::<HashMap<usize, Usage>>();
+
+        let percents = now.iter().filter_map(|x| {
+            let percent = x.usage.percent_since(
+                prev.get(&x.cpu)?
+            )?;
+
+            Some(CorePercent {
+                cpu: x.cpu,
+                percent,
+            })
+        })
+        .collect();
+
+        Some(percents)
+    }
+
+    type Rate = Vec<CorePercent>;
 }
 
+/// Probes the [`CorePercent`] of every
+/// logical cpu since the previous probe
+pub type CoresPercentProbe = RateProbe<CoresUsageProbe>;
+
 #[derive( 
     Deserialize,
     PartialOrd,
@@ -423,16 +943,44 @@ pub enum InfoError {
     CpuMhzNotFound,
 }
 
-impl Probe for Info {
-    type Error = InfoError;
+#[derive(
+    Clone,
+    Debug,
+)]
 
-    type Output = Self;
+/// Probes the [`Info`] from
+/// the /proc/cpuinfo file
+pub struct InfoProbe {
+    pub cpuinfo: PathBuf,
+}
 
-    async fn probe() -> Result<Self::Output, Self::Error> {
-        let info_file = std::env::var("INFO")
-            .unwrap_or("/proc/cpuinfo".into());
+impl InfoProbe {
+    pub fn new(cpuinfo: impl Into<PathBuf>) -> Self {
+        Self {
+            cpuinfo: cpuinfo.into(),
+        }
+    }
+}
 
-        let mut file = File::open(info_file).await?;
+impl From<&Source> for InfoProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.proc("INFO", "cpuinfo"))
+    }
+}
+
+impl Default for InfoProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for InfoProbe {
+    type Error = InfoError;
+
+    type Output = Info;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let mut file = File::open(&self.cpuinfo).await?;
 
         let mut cpuinfo = String::new();
 
@@ -469,7 +1017,7 @@ impl Probe for Info {
         let model = map.get("model name")
             .cloned();
 
-        Ok(Self {
+        Ok(Info {
             siblings,
             cores,
             model,
@@ -0,0 +1,335 @@
+use std::{
+    num::ParseIntError,
+    path::PathBuf,
+    time::Duration,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use monitor::{
+    probe::Probe,
+    rate::{CounterReset, Counters, RateProbe},
+};
+use thiserror::Error;
+
+use tokio::{
+    fs::File,
+    io::{self, AsyncBufReadExt, BufReader},
+};
+
+use super::Source;
+
+/// diskstats always counts in 512 byte
+/// sectors, whatever the device uses
+pub const SECTOR_SIZE: u64 = 512;
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// The io counters of a single block device,
+/// from its line in /proc/diskstats
+///
+/// Times are in milliseconds. The discard
+/// and flush counters are missing on older
+/// kernels.
+pub struct Disk {
+    pub sectors_discarded: Option<u64>,
+    pub discards_merged: Option<u64>,
+    pub discard_time: Option<u64>,
+    pub flush_time: Option<u64>,
+    pub discards: Option<u64>,
+    pub flushes: Option<u64>,
+    pub weighted_io_time: u64,
+    pub sectors_written: u64,
+    pub writes_merged: u64,
+    pub reads_merged: u64,
+    pub sectors_read: u64,
+    pub write_time: u64,
+    pub read_time: u64,
+    pub in_flight: u64,
+    pub io_time: u64,
+    pub writes: u64,
+    pub reads: u64,
+    pub minor: u32,
+    pub major: u32,
+    pub name: String,
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Every block device in /proc/diskstats
+pub struct Disks {
+    pub disks: Vec<Disk>,
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// The io of a [`Disk`] per second between
+/// two samples
+///
+/// await is the average milliseconds a
+/// request took, including queueing, and
+/// utilisation the percentage of time the
+/// device was busy
+pub struct DiskRate {
+    pub write_bytes_per_sec: f64,
+    pub read_bytes_per_sec: f64,
+    pub writes_per_sec: f64,
+    pub reads_per_sec: f64,
+    pub write_await: f64,
+    pub read_await: f64,
+    pub utilisation: f64,
+    pub in_flight: u64,
+    pub name: String,
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// The [`DiskRate`] of every block device
+pub struct DiskRates {
+    pub disks: Vec<DiskRate>,
+}
+
+#[derive(
+    Error,
+    Debug,
+)]
+
+pub enum Error {
+    #[error("io error occurred getting disks: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid diskstats line: {0}")]
+    InvalidLine(String),
+    #[error("failed to parse int: {0}")]
+    ParseInt(#[from] ParseIntError),
+    #[error(transparent)]
+    CounterReset(#[from] CounterReset),
+}
+
+impl Disk {
+    fn from_line(line: &str) -> Result<Self, Error> {
+        let fields = line.split_whitespace()
+            .collect::<Vec<&str>>();
+
+        if fields.len() < 14 {
+            return Err(Error::InvalidLine(line.to_string()));
+        }
+
+        let [
+            reads,
+            reads_merged,
+            sectors_read,
+            read_time,
+            writes,
+            writes_merged,
+            sectors_written,
+            write_time,
+            in_flight,
+            io_time,
+            weighted_io_time,
+        ] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]
+            .try_map(|x| fields[x].parse::<u64>())?;
+
+        let optional = |x: usize| {
+            fields.get(x)
+                .map(|x| x.parse::<u64>())
+                .transpose()
+        };
+
+        Ok(Self {
+            sectors_discarded: optional(16)?,
+            discards_merged: optional(15)?,
+            discard_time: optional(17)?,
+            flush_time: optional(19)?,
+            discards: optional(14)?,
+            flushes: optional(18)?,
+            minor: fields[1].parse()?,
+            major: fields[0].parse()?,
+            name: fields[2].to_string(),
+            weighted_io_time,
+            sectors_written,
+            writes_merged,
+            reads_merged,
+            sectors_read,
+            write_time,
+            read_time,
+            in_flight,
+            io_time,
+            writes,
+            reads,
+        })
+    }
+
+    /// The [`DiskRate`] since an earlier sample,
+    /// or None if the counters went backwards
+    pub fn rate_since(&self, prev: &Disk, elapsed: Duration) -> Option<DiskRate> {
+        let [
+            sectors_written,
+            sectors_read,
+            write_time,
+            read_time,
+            io_time,
+            writes,
+            reads,
+        ] = [
+            (self.sectors_written, prev.sectors_written),
+            (self.sectors_read, prev.sectors_read),
+            (self.write_time, prev.write_time),
+            (self.read_time, prev.read_time),
+            (self.io_time, prev.io_time),
+            (self.writes, prev.writes),
+            (self.reads, prev.reads),
+        ].try_map(|(now, prev)| now.checked_sub(prev))?;
+
+        let per_sec = |x: u64| match elapsed.as_secs_f64() {
+            secs if secs > 0.0 => x as f64 / secs,
+            _ => 0.0,
+        };
+
+        let await_ms = |time: u64, requests: u64| match requests {
+            0 => 0.0,
+            requests => time as f64 / requests as f64,
+        };
+
+        // io_time can run slightly ahead
+        // of the time between the samples
+        let utilisation = match elapsed.as_millis() {
+            0 => 0.0,
+            ms => (io_time as f64 / ms as f64 * 100.0).min(100.0),
+        };
+
+        Some(DiskRate {
+            write_bytes_per_sec: per_sec(sectors_written * SECTOR_SIZE),
+            read_bytes_per_sec: per_sec(sectors_read * SECTOR_SIZE),
+            writes_per_sec: per_sec(writes),
+            reads_per_sec: per_sec(reads),
+            write_await: await_ms(write_time, writes),
+            read_await: await_ms(read_time, reads),
+            in_flight: self.in_flight,
+            name: self.name.clone(),
+            utilisation,
+        })
+    }
+}
+
+impl Disks {
+    /// The [`DiskRates`] since an earlier sample,
+    /// disks that weren't in it or whose counters
+    /// were reset are left out
+    pub fn rate_since(&self, prev: &Disks, elapsed: Duration) -> DiskRates {
+        let disks = self.disks.iter().filter_map(|x| {
+            let prev = prev.disks.iter()
+                .find(|p| p.name == x.name)?;
+
+            x.rate_since(prev, elapsed)
+        })
+        .collect();
+
+        DiskRates {
+            disks,
+        }
+    }
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Disks`] from /proc/diskstats
+pub struct DisksProbe {
+    pub diskstats: PathBuf,
+}
+
+impl DisksProbe {
+    pub fn new(diskstats: impl Into<PathBuf>) -> Self {
+        Self {
+            diskstats: diskstats.into(),
+        }
+    }
+}
+
+impl From<&Source> for DisksProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.proc("DISKSTATS", "diskstats"))
+    }
+}
+
+impl Default for DisksProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for DisksProbe {
+    type Output = Disks;
+
+    type Error = Error;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let file = File::open(&self.diskstats).await?;
+
+        let mut lines = BufReader::new(file)
+            .lines();
+
+        let mut disks = Vec::new();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            disks.push(Disk::from_line(&line)?);
+        }
+
+        Ok(Disks {
+            disks,
+        })
+    }
+}
+
+impl Counters for DisksProbe {
+    fn rate_since(now: &Disks, prev: &Disks, elapsed: Duration) -> Option<DiskRates> {
+        Some(now.rate_since(prev, elapsed))
+    }
+
+    type Rate = DiskRates;
+}
+
+/// Probes the [`DiskRates`] since
+/// the previous probe
+pub type DiskRatesProbe = RateProbe<DisksProbe>;
@@ -0,0 +1,396 @@
+use std::{
+    collections::HashSet,
+    ffi::CString,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use monitor::probe::Probe;
+use thiserror::Error;
+
+use tokio::{
+    fs,
+    io,
+};
+
+use super::Source;
+
+/// How long statvfs gets before
+/// a mountpoint is given up on
+pub const STAT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Filesystems that don't store anything on a
+/// device, so their usage is rarely of interest
+pub const PSEUDO: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "proc",
+    "pstore",
+    "ramfs",
+    "rpc_pipefs",
+    "securityfs",
+    "sysfs",
+    "tmpfs",
+    "tracefs",
+];
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// The space and inodes of a filesystem, from
+/// statvfs. Sizes are in bytes, and available
+/// is what unprivileged users can still use.
+pub struct Usage {
+    pub inodes_used: u64,
+    pub inodes_free: u64,
+    pub available: u64,
+    pub inodes: u64,
+    pub used: u64,
+    pub free: u64,
+    pub size: u64,
+}
+
+impl Usage {
+    /// The percentage of the space users can
+    /// have that is used, like df shows it
+    pub fn percent(&self) -> f64 {
+        match self.used + self.available {
+            0 => 0.0,
+            x => self.used as f64 / x as f64 * 100.0,
+        }
+    }
+
+    pub fn inodes_percent(&self) -> f64 {
+        match self.inodes {
+            0 => 0.0,
+            x => self.inodes_used as f64 / x as f64 * 100.0,
+        }
+    }
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// A mounted filesystem, from /proc/mounts
+///
+/// usage is missing when the mountpoint
+/// couldn't be stat'd, e.g. a hung nfs
+/// mount or one hidden by permissions,
+/// with the reason in error
+pub struct Filesystem {
+    pub usage: Option<Usage>,
+    pub error: Option<String>,
+    pub mountpoint: PathBuf,
+    pub read_only: bool,
+    pub device: String,
+    pub fstype: String,
+    pub pseudo: bool,
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Every mounted filesystem
+pub struct Filesystems {
+    pub filesystems: Vec<Filesystem>,
+}
+
+#[derive(
+    Error,
+    Debug,
+)]
+
+pub enum Error {
+    #[error("io error occurred getting filesystems: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid mounts line: {0}")]
+    InvalidLine(String),
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Filesystems`] in /proc/mounts,
+/// stat'ing every mountpoint under root at
+/// once, each for up to timeout. A mountpoint
+/// mounted over more than once is only
+/// stat'd once.
+pub struct FilesystemsProbe {
+    pub mounts: PathBuf,
+    pub root: PathBuf,
+    pub timeout: Duration,
+    // statvfs can't be cancelled, so a hung
+    // mountpoint isn't stat'd again until its
+    // last statvfs returns, tying up a single
+    // blocking thread at most
+    running: Arc<Mutex<HashSet<PathBuf>>>,
+    // statvfs, unless a test needs one
+    // that's slow or counts its calls
+    pub(crate) stat: fn(&Path) -> io::Result<Usage>,
+}
+
+impl FilesystemsProbe {
+    pub fn new(
+        mounts: impl Into<PathBuf>,
+        root: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            running: Arc::new(Mutex::new(HashSet::new())),
+            timeout: STAT_TIMEOUT,
+            stat: statvfs,
+            mounts: mounts.into(),
+            root: root.into(),
+        }
+    }
+
+    async fn usage(&self, path: PathBuf) -> Result<Usage, String> {
+        let added = self.running.lock()
+            .unwrap_or_else(|x| x.into_inner())
+            .insert(path.clone());
+
+        if !added {
+            return Err("still waiting on a statvfs from an earlier probe".to_string());
+        }
+
+        let running = self.running.clone();
+        let stat = self.stat;
+
+        let task = tokio::task::spawn_blocking(move || {
+            let res = stat(&path);
+
+            running.lock()
+                .unwrap_or_else(|x| x.into_inner())
+                .remove(&path);
+
+            res
+        });
+
+        match tokio::time::timeout(self.timeout, task).await {
+            Ok(Ok(res)) => res.map_err(|e| e.to_string()),
+            Ok(Err(e)) => Err(e.to_string()),
+            Err(_) => Err(format!("statvfs timed out after {:?}", self.timeout)),
+        }
+    }
+}
+
+impl From<&Source> for FilesystemsProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.proc("MOUNTS", "mounts"), &source.rootfs)
+    }
+}
+
+impl Default for FilesystemsProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for FilesystemsProbe {
+    type Output = Filesystems;
+
+    type Error = Error;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let mounts = fs::read_to_string(&self.mounts).await?;
+
+        let mut filesystems = Vec::new();
+        let mut paths = Vec::<PathBuf>::new();
+        let mut indices = Vec::new();
+
+        for line in mounts.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+
+            let [
+                device,
+                mountpoint,
+                fstype,
+                options,
+            ] = [(); 4].try_map(|_| {
+                fields.next().ok_or(Error::InvalidLine(line.to_string()))
+            })?;
+
+            let mountpoint = PathBuf::from(unescape(mountpoint));
+
+            let path = self.root.join(
+                mountpoint.strip_prefix("/").unwrap_or(&mountpoint)
+            );
+
+            // stacked and bind mounts share a
+            // mountpoint, and so its usage
+            indices.push(match paths.iter().position(|x| *x == path) {
+                Some(i) => i,
+                None => {
+                    paths.push(path);
+                    paths.len() - 1
+                }
+            });
+
+            filesystems.push(Filesystem {
+                read_only: options.split(',').any(|x| x == "ro"),
+                pseudo: PSEUDO.contains(&fstype),
+                device: unescape(device),
+                fstype: fstype.to_string(),
+                usage: None,
+                error: None,
+                mountpoint,
+            });
+        }
+
+        // statvfs blocks, and can for a long
+        // time on network filesystems
+        let usages = futures_util::future::join_all(
+            paths.into_iter().map(|x| self.usage(x))
+        )
+        .await;
+
+        for (x, i) in filesystems.iter_mut().zip(indices) {
+            match &usages[i] {
+                Ok(usage) => x.usage = Some(*usage),
+                Err(e) => x.error = Some(e.clone()),
+            }
+        }
+
+        Ok(Filesystems {
+            filesystems,
+        })
+    }
+}
+
+/// Undoes the octal escapes /proc/mounts uses
+/// for spaces, tabs, newlines and backslashes
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 4)
+            .filter(|_| bytes[i] == b'\\')
+            .and_then(|x| std::str::from_utf8(x).ok())
+            .and_then(|x| u8::from_str_radix(x, 8).ok());
+
+        match octal {
+            Some(x) => {
+                out.push(x);
+                i += 4;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn statvfs(path: &Path) -> io::Result<Usage> {
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(io::Error::other)?;
+
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+
+    // SAFETY: path is nul terminated and stat
+    // is only read once statvfs filled it in
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        stat.assume_init()
+    };
+
+    let frsize = stat.f_frsize as u64;
+
+    let blocks = stat.f_blocks as u64;
+    let bfree = stat.f_bfree as u64;
+    let files = stat.f_files as u64;
+    let ffree = stat.f_ffree as u64;
+
+    Ok(Usage {
+        inodes_used: files.saturating_sub(ffree),
+        available: stat.f_bavail as u64 * frsize,
+        used: blocks.saturating_sub(bfree) * frsize,
+        free: bfree * frsize,
+        size: blocks * frsize,
+        inodes_free: ffree,
+        inodes: files,
+    })
+}
+
+#[derive(
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Filtering of [`Filesystems`], pseudo
+/// filesystems are kept unless pseudo is false
+pub struct Query {
+    pub fstype: Option<String>,
+    pub pseudo: Option<bool>,
+}
+
+impl Filesystems {
+    pub fn query(mut self, query: &Query) -> Self {
+        if query.pseudo == Some(false) {
+            self.filesystems.retain(|x| !x.pseudo);
+        }
+
+        if let Some(fstype) = &query.fstype {
+            self.filesystems.retain(|x| x.fstype == *fstype);
+        }
+
+        self
+    }
+}
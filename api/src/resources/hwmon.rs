@@ -0,0 +1,350 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use monitor::probe::Probe;
+use thiserror::Error;
+
+use tokio::{
+    fs::{self, read_dir},
+    io,
+};
+
+use super::Source;
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+    Hash,
+)]
+
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    Temp,
+    Fan,
+    In,
+    Power,
+    Curr,
+}
+
+impl FromStr for ChannelKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "temp" => Ok(Self::Temp),
+            "fan" => Ok(Self::Fan),
+            "in" => Ok(Self::In),
+            "power" => Ok(Self::Power),
+            "curr" => Ok(Self::Curr),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+)]
+
+/// A single channel of a hwmon chip, e.g. temp1
+///
+/// Values are in the kernel's hwmon units:
+/// millidegrees Celsius, RPM, millivolts,
+/// microwatts and milliamperes
+pub struct Channel {
+    pub kind: ChannelKind,
+    pub label: Option<String>,
+    pub emergency: Option<i64>,
+    pub input: Option<i64>,
+    pub lcrit: Option<i64>,
+    pub crit: Option<i64>,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    /// The limits whose alarm
+    /// is currently raised
+    pub alarms: Vec<String>,
+    pub index: usize,
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// A hwmon chip (coretemp, k10temp,
+/// nvme, acpitz...) and its channels
+pub struct Chip {
+    pub channels: Vec<Channel>,
+    pub path: PathBuf,
+    pub name: String,
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Every hwmon chip found
+/// under /sys/class/hwmon
+pub struct Sensors {
+    pub chips: Vec<Chip>,
+}
+
+#[derive(
+    Error,
+    Debug,
+)]
+
+pub enum Error {
+    #[error("io error occurred getting hwmon sensors: {0}")]
+    Io(#[from] io::Error),
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Sensors`] from
+/// the /sys/class/hwmon directory
+pub struct SensorsProbe {
+    pub dir: PathBuf,
+}
+
+impl SensorsProbe {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+        }
+    }
+
+    /// Finds the directories of every chip
+    /// with the given name, e.g. coretemp
+    pub async fn find(&self, name: &str) -> Result<Vec<PathBuf>, Error> {
+        let chips = match chips(&self.dir).await {
+            // no hwmon class at all, so
+            // there can't be any chips
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(Vec::new());
+            }
+            x => x?,
+        };
+
+        let chips = chips.into_iter()
+            .filter(|(x, _)| x == name)
+            .map(|(_, x)| x)
+            .collect();
+
+        Ok(chips)
+    }
+}
+
+impl From<&Source> for SensorsProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.sys("HWMON", "class/hwmon"))
+    }
+}
+
+impl Default for SensorsProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for SensorsProbe {
+    type Output = Sensors;
+
+    type Error = Error;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let mut sensors = Vec::new();
+
+        for (name, path) in chips(&self.dir).await? {
+            let channels = channels(&path)
+                .await?;
+
+            sensors.push(Chip {
+                channels,
+                path,
+                name,
+            });
+        }
+
+        Ok(Sensors {
+            chips: sensors,
+        })
+    }
+}
+
+/// The name and attribute directory of
+/// every chip, sorted by directory
+async fn chips(dir: &Path) -> Result<Vec<(String, PathBuf)>, Error> {
+    let mut rdir = read_dir(dir).await?;
+
+    let mut chips = Vec::new();
+
+    while let Some(ent) = rdir.next_entry().await? {
+        // the entries are symlinks into
+        // /sys/devices, so follow them,
+        // skipping any that dangle
+        match fs::metadata(ent.path()).await {
+            Ok(x) if x.is_dir() => {}
+            _ => continue,
+        }
+
+        // older drivers keep their attributes
+        // in the device directory instead
+        let path = ent.path();
+        let device = path.join("device");
+
+        let (name, path) = match read(&path.join("name")).await {
+            Some(name) => (name, path),
+            None => match read(&device.join("name")).await {
+                Some(name) => (name, device),
+                None => (
+                    ent.file_name()
+                        .to_string_lossy()
+                        .to_string(),
+                    path,
+                ),
+            },
+        };
+
+        chips.push((name, path));
+    }
+
+    chips.sort_by(|a, b| a.1.cmp(&b.1));
+
+    Ok(chips)
+}
+
+/// Splits an attribute like temp1_crit_alarm
+/// into its kind, index and attribute
+fn split(attr: &str) -> Option<(ChannelKind, usize, &str)> {
+    let (channel, attr) = attr.split_once('_')?;
+
+    let digits = channel.find(|x: char| x.is_ascii_digit())?;
+
+    let (kind, index) = channel.split_at(digits);
+
+    Some((
+        kind.parse().ok()?,
+        index.parse().ok()?,
+        attr,
+    ))
+}
+
+async fn channels(chip: &Path) -> Result<Vec<Channel>, Error> {
+    let mut rdir = read_dir(chip).await?;
+
+    let mut attrs = BTreeMap::<(ChannelKind, usize), HashMap<String, PathBuf>>::new();
+
+    while let Some(ent) = rdir.next_entry().await? {
+        let name = ent.file_name()
+            .to_string_lossy()
+            .to_string();
+
+        let Some((kind, index, attr)) = split(&name) else {
+            continue;
+        };
+
+        attrs.entry((kind, index))
+            .or_default()
+            .insert(attr.to_string(), ent.path());
+    }
+
+    let mut channels = Vec::new();
+
+    for ((kind, index), attrs) in attrs {
+        let value = |attr: &str| {
+            let path = attrs.get(attr)
+                .cloned();
+
+            async move {
+                read(&path?).await?
+                    .parse::<i64>()
+                    .ok()
+            }
+        };
+
+        // power is usually only
+        // reported as an average
+        let input = match value("input").await {
+            None => value("average").await,
+            x => x,
+        };
+
+        let label = match attrs.get("label") {
+            Some(x) => read(x).await,
+            None => None,
+        };
+
+        let mut alarms = Vec::new();
+
+        for (attr, path) in &attrs {
+            if !attr.ends_with("alarm") {
+                continue;
+            }
+
+            if read(path).await.as_deref() == Some("1") {
+                let alarm = attr.strip_suffix("_alarm")
+                    .unwrap_or(attr);
+
+                alarms.push(alarm.to_string());
+            }
+        }
+
+        alarms.sort();
+
+        channels.push(Channel {
+            emergency: value("emergency").await,
+            lcrit: value("lcrit").await,
+            crit: value("crit").await,
+            min: value("min").await,
+            max: value("max").await,
+            kind,
+            alarms,
+            label,
+            input,
+            index,
+        });
+    }
+
+    Ok(channels)
+}
+
+/// Reads an attribute, sensors that are
+/// offline often fail to read so those
+/// are treated as missing
+async fn read(path: &Path) -> Option<String> {
+    fs::read_to_string(path).await
+        .ok()
+        .map(|x| x.trim().to_string())
+}
@@ -0,0 +1,164 @@
+use std::{
+    num::{ParseFloatError, ParseIntError},
+    path::PathBuf,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use monitor::probe::Probe;
+use thiserror::Error;
+
+use super::Source;
+
+use tokio::{
+    io::{
+        AsyncBufReadExt,
+        BufReader,
+        self,
+    },
+    fs::File,
+};
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// The load average of the system over 1, 5 and 15
+/// minutes. Taken from the /proc/loadavg file /
+/// callback, along with the amount of runnable and
+/// total tasks and the most recently created pid.
+pub struct LoadAvg {
+    pub fifteen: f64,
+    pub running: u64,
+    pub last_pid: u64,
+    pub total: u64,
+    pub five: f64,
+    pub one: f64,
+}
+
+#[derive(
+    Error,
+    Debug,
+)]
+
+pub enum Error {
+    #[error("the loadavg received has an invalid amount of columns")]
+    InvalidLoadAvg,
+    #[error("the loadavg tasks column isn't formatted as running/total")]
+    InvalidTasks,
+    #[error("io error occurred getting loadavg: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to parse float: {0}")]
+    ParseFloat(ParseFloatError),
+    #[error("failed to parse int: {0}")]
+    ParseInt(ParseIntError),
+    #[error("loadavg file is empty")]
+    Empty,
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`LoadAvg`] from
+/// the /proc/loadavg file
+pub struct LoadAvgProbe {
+    pub file: PathBuf,
+}
+
+impl LoadAvgProbe {
+    pub fn new(file: impl Into<PathBuf>) -> Self {
+        Self {
+            file: file.into(),
+        }
+    }
+}
+
+impl From<&Source> for LoadAvgProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.proc("LOADAVG", "loadavg"))
+    }
+}
+
+impl Default for LoadAvgProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for LoadAvgProbe {
+    type Output = LoadAvg;
+
+    type Error = Error;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let file = File::open(&self.file).await?;
+
+        let mut loadavg = String::new();
+
+        BufReader::new(file).read_line(&mut loadavg)
+            .await?;
+
+        if loadavg.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        let [
+            one,
+            five,
+            fifteen,
+            tasks,
+            last_pid,
+        ]: [
+            &str; 5
+        ] = loadavg.split_whitespace()
+            .collect::<Vec<&str>>()
+            .try_into()
+            .map_err(|_| Error::InvalidLoadAvg)?;
+
+        let [
+            one,
+            five,
+            fifteen,
+        ] = [
+            one,
+            five,
+            fifteen,
+        ].try_map(|x| x.parse::<f64>()
+            .map_err(Error::ParseFloat))?;
+
+        let (running, total) = tasks.split_once('/')
+            .ok_or(Error::InvalidTasks)?;
+
+        let [
+            running,
+            total,
+            last_pid,
+        ] = [
+            running,
+            total,
+            last_pid,
+        ].try_map(|x| x.parse::<u64>()
+            .map_err(Error::ParseInt))?;
+
+        Ok(LoadAvg {
+            last_pid,
+            fifteen,
+            running,
+            total,
+            five,
+            one,
+        })
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     collections::HashMap,
+    path::PathBuf,
     num::{
         IntErrorKind,
         ParseIntError,
@@ -8,6 +9,8 @@ use std::{
 
 use monitor::probe::Probe;
 
+use super::Source;
+
 use serde::{
     Deserialize,
     Serialize,
@@ -89,16 +92,44 @@ pub enum Error {
     KeyEmpty(String),
 }
 
-impl Probe for Memory {
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Memory`] from
+/// the /proc/meminfo file
+pub struct MemoryProbe {
+    pub meminfo: PathBuf,
+}
+
+impl MemoryProbe {
+    pub fn new(meminfo: impl Into<PathBuf>) -> Self {
+        Self {
+            meminfo: meminfo.into(),
+        }
+    }
+}
+
+impl From<&Source> for MemoryProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.proc("MEMINFO", "meminfo"))
+    }
+}
+
+impl Default for MemoryProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for MemoryProbe {
     type Output = Memory;
 
     type Error = Error;
 
-    async fn probe() -> Result<Self::Output, Self::Error> {
-        let meminfo_file = std::env::var("MEMINFO")
-            .unwrap_or("/proc/meminfo".into());
-
-        let file = File::open(meminfo_file).await?;
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let file = File::open(&self.meminfo).await?;
 
         let bufreader = BufReader::new(file);
 
@@ -191,7 +222,7 @@ impl Probe for Memory {
             )
         })?;
 
-        Ok(Self {
+        Ok(Memory {
             inactive_anonymous,
             active_anonymous,
             inactive_file,
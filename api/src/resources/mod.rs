@@ -1,4 +1,139 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use monitor::rate::{Counters, RateProbe};
+
 pub mod network;
+pub mod loadavg;
 pub mod memory;
+pub mod disk;
+pub mod filesystem;
+pub mod pressure;
+pub mod process;
+pub mod socket;
 pub mod uptime;
+pub mod hwmon;
 pub mod cpu;
+
+// The env variables that move a single
+// file or dir of procfs/sysfs elsewhere
+const OVERRIDES: [&str; 11] = [
+    "DISKSTATS",
+    "LOADAVG",
+    "MEMINFO",
+    "PRESSURE",
+    "PROC_NET",
+    "MOUNTS",
+    "UPTIME",
+    "STAT",
+    "INFO",
+    "HWMON",
+    "NET",
+];
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Where the kernel file systems probes
+/// read from are mounted.
+///
+/// Defaults to the host's own /proc and
+/// /sys, but can point anywhere else, e.g.
+/// /host/proc when monitoring the host
+/// from inside a container.
+pub struct Source {
+    pub procfs: PathBuf,
+    pub sysfs: PathBuf,
+    /// Where the host's root is mounted, since
+    /// the mountpoints in procfs are the host's
+    pub rootfs: PathBuf,
+    pub passwd: PathBuf,
+    /// A coretemp hwmon dir to use instead
+    /// of looking one up
+    pub coretemp: Option<PathBuf>,
+    /// Single files or dirs moved
+    /// elsewhere, by env variable
+    pub overrides: HashMap<String, PathBuf>,
+}
+
+/// Same as [`Source::from_env`]
+impl Default for Source {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+impl Source {
+    /// Reads from exactly these roots,
+    /// whatever the env says
+    pub fn new(
+        procfs: impl Into<PathBuf>,
+        sysfs: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            procfs: procfs.into(),
+            sysfs: sysfs.into(),
+            rootfs: "/".into(),
+            passwd: "/etc/passwd".into(),
+            coretemp: None,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Uses the PROCFS, SYSFS, ROOTFS and PASSWD
+    /// env variables if they are set, along with
+    /// CORETEMP and any single file override
+    /// (e.g. MEMINFO)
+    pub fn from_env() -> Self {
+        let var = |env: &str| std::env::var(env)
+            .map(PathBuf::from)
+            .ok();
+
+        let mut source = Self::new(
+            var("PROCFS").unwrap_or("/proc".into()),
+            var("SYSFS").unwrap_or("/sys".into()),
+        );
+
+        if let Some(rootfs) = var("ROOTFS") {
+            source.rootfs = rootfs;
+        }
+
+        if let Some(passwd) = var("PASSWD") {
+            source.passwd = passwd;
+        }
+
+        source.coretemp = var("CORETEMP");
+
+        source.overrides = OVERRIDES.iter()
+            .filter_map(|x| Some((x.to_string(), var(x)?)))
+            .collect();
+
+        source
+    }
+
+    /// A path under procfs, unless
+    /// `name` is overridden
+    pub fn proc(&self, name: &str, path: &str) -> PathBuf {
+        self.overrides.get(name)
+            .cloned()
+            .unwrap_or_else(|| self.procfs.join(path))
+    }
+
+    /// A path under sysfs, unless
+    /// `name` is overridden
+    pub fn sys(&self, name: &str, path: &str) -> PathBuf {
+        self.overrides.get(name)
+            .cloned()
+            .unwrap_or_else(|| self.sysfs.join(path))
+    }
+}
+
+impl<P> From<&Source> for RateProbe<P>
+where
+    P: Counters + for<'a> From<&'a Source>,
+{
+    fn from(source: &Source) -> Self {
+        Self::new(source.into())
+    }
+}
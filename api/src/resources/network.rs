@@ -1,27 +1,35 @@
 use std::{
     fmt::{Debug, Display},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
     path::{Path, PathBuf},
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     str::FromStr,
+    time::Duration,
 };
 
 use tokio::{
     io::{self, AsyncReadExt},
-    fs::{read_dir, File},
+    fs::{self, read_dir, File},
 };
 
 use serde::{Deserialize, Serialize};
 use futures_util::TryStreamExt;
-use monitor::probe::Probe;
+use monitor::{
+    probe::Probe,
+    rate::{CounterReset, Counters, RateProbe},
+};
 use anyhow::anyhow;
 
 use tokio_stream::wrappers::ReadDirStream;
 
+use super::Source;
+
 #[derive(
     PartialEq, PartialOrd,
     Deserialize,
     Serialize,
     Debug,
+    Clone,
 )]
 
 pub struct Interface {
@@ -35,10 +43,36 @@ pub struct Interface {
     PartialEq, PartialOrd,
     Deserialize,
     Serialize,
+    Default,
     Debug,
+    Clone,
 )]
 
+/// The counters in the statistics directory of
+/// an interface. Only the packet and byte counts
+/// are always there, drivers leave out the rest
+/// as they like.
 pub struct Stats {
+    pub tx_heartbeat_errors: Option<usize>,
+    pub tx_carrier_errors: Option<usize>,
+    pub tx_aborted_errors: Option<usize>,
+    pub tx_window_errors: Option<usize>,
+    pub rx_length_errors: Option<usize>,
+    pub rx_missed_errors: Option<usize>,
+    pub rx_frame_errors: Option<usize>,
+    pub rx_over_errors: Option<usize>,
+    pub rx_fifo_errors: Option<usize>,
+    pub tx_fifo_errors: Option<usize>,
+    pub rx_crc_errors: Option<usize>,
+    pub rx_compressed: Option<usize>,
+    pub tx_compressed: Option<usize>,
+    pub rx_nohandler: Option<usize>,
+    pub collisions: Option<usize>,
+    pub rx_dropped: Option<usize>,
+    pub tx_dropped: Option<usize>,
+    pub rx_errors: Option<usize>,
+    pub tx_errors: Option<usize>,
+    pub multicast: Option<usize>,
     pub tx_packets: usize,
     pub rx_packets: usize,
     pub rx_bytes: usize,
@@ -49,12 +83,21 @@ pub struct Stats {
     PartialEq, PartialOrd,
     Deserialize,
     Serialize,
+    Default,
     Debug,
+    Clone,
 )]
 
+/// The RFC 2863 operational state
+/// of an interface
 pub enum OperState {
+    #[default]
     Unknown,
+    NotPresent,
     Down,
+    LowerLayerDown,
+    Testing,
+    Dormant,
     Up,
 }
 
@@ -64,7 +107,11 @@ impl FromStr for OperState {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "unknown" => Ok(Self::Unknown),
+            "notpresent" => Ok(Self::NotPresent),
             "down" => Ok(Self::Down),
+            "lowerlayerdown" => Ok(Self::LowerLayerDown),
+            "testing" => Ok(Self::Testing),
+            "dormant" => Ok(Self::Dormant),
             "up" => Ok(Self::Up),
             s => Err(anyhow!(
                 "invalid OperState '{}'",
@@ -78,7 +125,11 @@ impl Display for OperState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Unknown => write!(f, "unknown"),
+            Self::NotPresent => write!(f, "notpresent"),
             Self::Down => write!(f, "down"),
+            Self::LowerLayerDown => write!(f, "lowerlayerdown"),
+            Self::Testing => write!(f, "testing"),
+            Self::Dormant => write!(f, "dormant"),
             Self::Up => write!(f, "up"),
         }
     }
@@ -89,25 +140,115 @@ impl Display for OperState {
     Deserialize,
     Serialize,
     Debug,
+    Clone,
 )]
 
 pub struct Info {
+    pub carrier_changes: Option<usize>,
+    pub addresses: Vec<Address>,
+    pub carrier: Option<bool>,
+    pub duplex: Option<Duplex>,
     pub operstate: OperState,
+    #[serde(rename = "type")]
+    pub kind: InterfaceType,
+    pub mtu: Option<usize>,
     pub address: String,
     pub speed: usize,
 }
 
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// An ip address assigned to an
+/// interface, with its prefix length
+pub struct Address {
+    pub address: IpAddr,
+    pub prefix: u8,
+}
+
+#[derive(
+    PartialEq, PartialOrd,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+#[serde(rename_all = "lowercase")]
+pub enum Duplex {
+    Full,
+    Half,
+    Unknown,
+}
+
+impl FromStr for Duplex {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(Self::Full),
+            "half" => Ok(Self::Half),
+            "unknown" => Ok(Self::Unknown),
+            s => Err(anyhow!(
+                "invalid Duplex '{}'",
+                s,
+            )),
+        }
+    }
+}
+
+#[derive(
+    PartialEq, PartialOrd,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// What kind of device an interface is,
+/// anything else without a physical device
+/// behind it is just virtual
+#[serde(rename_all = "lowercase")]
+pub enum InterfaceType {
+    Physical,
+    Loopback,
+    Bridge,
+    Bond,
+    Vlan,
+    Veth,
+    Tun,
+    #[default]
+    Virtual,
+}
+
 #[derive(
     Deserialize,
     PartialEq,
     Serialize,
     Default,
     Debug,
+    Clone,
 )]
 
+/// Every interface under /sys/class/net
+///
+/// An interface that couldn't be read is
+/// left out, with its error by name in
+/// errors, so one odd device doesn't hide
+/// the rest
 pub struct Network {
+    pub errors: BTreeMap<String, String>,
     pub interfaces: Vec<Interface>,
-    // Possibly other stuff
 }
 
 #[derive(
@@ -137,20 +278,62 @@ pub enum Error {
         source: anyhow::Error,
         stat: String,
     },
+    #[error(transparent)]
+    CounterReset(#[from] CounterReset),
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Network`] from the
+/// /sys/class/net directory, and the
+/// addresses from /proc/net
+pub struct NetworkProbe {
+    pub proc_net: PathBuf,
+    pub dir: PathBuf,
+}
+
+impl NetworkProbe {
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        proc_net: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            proc_net: proc_net.into(),
+            dir: dir.into(),
+        }
+    }
 }
 
-impl Probe for Network {
-    type Output = Self;
+impl From<&Source> for NetworkProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(
+            source.sys("NET", "class/net"),
+            source.proc("PROC_NET", "net"),
+        )
+    }
+}
+
+impl Default for NetworkProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for NetworkProbe {
+    type Output = Network;
 
     type Error = Error;
 
-    async fn probe() -> Result<Self::Output, Self::Error> {
-        let nw_dir = std::env::var("NET")
-            .unwrap_or("/sys/class/net/".into());
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let mut rdir = read_dir(&self.dir).await?;
 
-        let mut rdir = read_dir(nw_dir).await?;
+        let mut addresses = addresses(&self.proc_net).await;
 
         let mut interfaces = Vec::new();
+        let mut errors = BTreeMap::new();
 
         while let Some(ent) = rdir.next_entry().await? {
             if ent.file_type().await?.is_file() {
@@ -163,13 +346,20 @@ impl Probe for Network {
 
             let path = ent.path();
 
-            let stats = get_stats(
-                &path
-            ).await?;
+            let (stats, mut info) = match tokio::try_join!(
+                get_stats(&path),
+                get_info(&path),
+            ) {
+                Ok(x) => x,
+                Err(e) => {
+                    errors.insert(name, e.to_string());
+
+                    continue;
+                }
+            };
 
-            let info = get_info(
-                &path
-            ).await?;
+            info.addresses = addresses.remove(&name)
+                .unwrap_or_default();
 
             let interface = Interface {
                 stats,
@@ -181,12 +371,284 @@ impl Probe for Network {
             interfaces.push(interface);
         }
 
-        Ok(Self {
+        interfaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Network {
             interfaces,
+            errors,
+        })
+    }
+}
+
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// The traffic of an [`Interface`] per second
+/// between two samples
+///
+/// Utilisation is the percentage of the link
+/// speed used in each direction, missing when
+/// the speed isn't known
+pub struct InterfaceRate {
+    pub operstate: OperState,
+    #[serde(rename = "type")]
+    pub kind: InterfaceType,
+    pub rx_utilisation: Option<f64>,
+    pub tx_utilisation: Option<f64>,
+    pub rx_packets_per_sec: f64,
+    pub tx_packets_per_sec: f64,
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
+    pub name: String,
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// The [`InterfaceRate`] of every interface,
+/// with the errors of the latest sample
+pub struct NetworkRates {
+    pub errors: BTreeMap<String, String>,
+    pub interfaces: Vec<InterfaceRate>,
+}
+
+/// The increase of a counter, some drivers only
+/// keep 32 bits so those wrap around. Anything
+/// else going backwards was reset, e.g. the
+/// interface was recreated.
+fn delta(now: usize, prev: usize) -> Option<usize> {
+    const WRAP: usize = u32::MAX as usize;
+
+    // how close to either end of the 32 bits
+    // the samples have to be for a drop to
+    // be a wrap rather than a reset
+    const NEAR: usize = WRAP / 8;
+
+    match now.checked_sub(prev) {
+        Some(x) => Some(x),
+        None if prev <= WRAP && prev > WRAP - NEAR && now < NEAR => {
+            Some(WRAP - prev + now + 1)
+        }
+        None => None,
+    }
+}
+
+impl Interface {
+    /// The [`InterfaceRate`] since an earlier
+    /// sample, or None if a counter was reset
+    pub fn rate_since(&self, prev: &Interface, elapsed: Duration) -> Option<InterfaceRate> {
+        let [
+            rx_packets,
+            tx_packets,
+            rx_bytes,
+            tx_bytes,
+        ] = [
+            (self.stats.rx_packets, prev.stats.rx_packets),
+            (self.stats.tx_packets, prev.stats.tx_packets),
+            (self.stats.rx_bytes, prev.stats.rx_bytes),
+            (self.stats.tx_bytes, prev.stats.tx_bytes),
+        ].try_map(|(now, prev)| delta(now, prev))?;
+
+        let per_sec = |x: usize| match elapsed.as_secs_f64() {
+            secs if secs > 0.0 => x as f64 / secs,
+            _ => 0.0,
+        };
+
+        // speed is in Mbit/s
+        let utilisation = |bytes_per_sec: f64| match self.info.speed {
+            0 => None,
+            speed => Some(bytes_per_sec * 8.0 / (speed as f64 * 1e6) * 100.0),
+        };
+
+        let rx_bytes_per_sec = per_sec(rx_bytes);
+        let tx_bytes_per_sec = per_sec(tx_bytes);
+
+        Some(InterfaceRate {
+            rx_utilisation: utilisation(rx_bytes_per_sec),
+            tx_utilisation: utilisation(tx_bytes_per_sec),
+            rx_packets_per_sec: per_sec(rx_packets),
+            tx_packets_per_sec: per_sec(tx_packets),
+            operstate: self.info.operstate.clone(),
+            name: self.name.clone(),
+            kind: self.info.kind,
+            rx_bytes_per_sec,
+            tx_bytes_per_sec,
         })
     }
 }
 
+impl Network {
+    /// The [`NetworkRates`] since an earlier sample,
+    /// interfaces that weren't in it or whose
+    /// counters were reset are left out
+    pub fn rate_since(&self, prev: &Network, elapsed: Duration) -> NetworkRates {
+        let interfaces = self.interfaces.iter().filter_map(|x| {
+            let prev = prev.interfaces.iter()
+                .find(|p| p.name == x.name)?;
+
+            x.rate_since(prev, elapsed)
+        })
+        .collect();
+
+        NetworkRates {
+            errors: self.errors.clone(),
+            interfaces,
+        }
+    }
+}
+
+impl Counters for NetworkProbe {
+    fn rate_since(now: &Network, prev: &Network, elapsed: Duration) -> Option<NetworkRates> {
+        Some(now.rate_since(prev, elapsed))
+    }
+
+    type Rate = NetworkRates;
+}
+
+/// Probes the [`NetworkRates`] since
+/// the previous probe
+pub type NetworkRatesProbe = RateProbe<NetworkProbe>;
+
+#[derive(
+    Deserialize,
+    PartialEq,
+    Serialize,
+    Debug,
+    Clone,
+)]
+
+#[serde(tag = "event", rename_all = "lowercase")]
+pub enum EventKind {
+    Added {
+        operstate: OperState,
+    },
+    Removed,
+    Link {
+        from: OperState,
+        to: OperState,
+    },
+}
+
+#[derive(
+    Deserialize,
+    PartialEq,
+    Serialize,
+    Debug,
+    Clone,
+)]
+
+/// A change between two [`Network`] samples,
+/// timestamp is in milliseconds since the epoch
+pub struct NetworkEvent {
+    #[serde(flatten)]
+    pub kind: EventKind,
+    pub interface: String,
+    pub timestamp: u64,
+}
+
+impl Network {
+    /// The interfaces that appeared, disappeared
+    /// or changed operstate since an earlier sample
+    ///
+    /// Interfaces that couldn't be read in either
+    /// sample are still there, their operstate
+    /// just isn't known
+    pub fn events_since(&self, prev: &Network, timestamp: u64) -> Vec<NetworkEvent> {
+        let event = |interface: &str, kind| NetworkEvent {
+            interface: interface.to_string(),
+            timestamp,
+            kind,
+        };
+
+        let mut events = Vec::new();
+
+        for x in &prev.interfaces {
+            if !self.interfaces.iter().any(|now| now.name == x.name)
+                && !self.errors.contains_key(&x.name)
+            {
+                events.push(event(&x.name, EventKind::Removed));
+            }
+        }
+
+        for x in &self.interfaces {
+            let operstate = x.info.operstate.clone();
+
+            match prev.interfaces.iter().find(|prev| prev.name == x.name) {
+                None if prev.errors.contains_key(&x.name) => (),
+                None => events.push(event(&x.name, EventKind::Added {
+                    operstate,
+                })),
+                Some(prev) if prev.info.operstate != operstate => {
+                    events.push(event(&x.name, EventKind::Link {
+                        from: prev.info.operstate.clone(),
+                        to: operstate,
+                    }));
+                }
+                Some(_) => (),
+            }
+        }
+
+        events
+    }
+}
+
+#[derive(
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Filtering of the interfaces, e.g.
+/// ?type=physical&state=up
+pub struct Query {
+    #[serde(rename = "type")]
+    pub kind: Option<InterfaceType>,
+    pub state: Option<String>,
+}
+
+impl Query {
+    fn matches(&self, kind: InterfaceType, operstate: &OperState) -> bool {
+        self.kind.is_none_or(|x| x == kind)
+            && self.state.as_ref().is_none_or(|x| *x == operstate.to_string())
+    }
+}
+
+impl Network {
+    pub fn query(mut self, query: &Query) -> Self {
+        self.interfaces.retain(|x| {
+            query.matches(x.info.kind, &x.info.operstate)
+        });
+
+        self
+    }
+}
+
+impl NetworkRates {
+    pub fn query(mut self, query: &Query) -> Self {
+        self.interfaces.retain(|x| {
+            query.matches(x.kind, &x.operstate)
+        });
+
+        self
+    }
+}
 
 async fn get_stats(interface: &Path) -> Result<Stats, Error> {
     let path = format!("{}/statistics", interface.to_string_lossy());
@@ -206,9 +668,6 @@ async fn get_stats(interface: &Path) -> Result<Stats, Error> {
         })
         .try_collect::<HashMap<String, PathBuf>>().await?;
 
-    // TODO
-    // add more stats
-
     let [
         rx_packets,
         tx_packets,
@@ -230,7 +689,34 @@ async fn get_stats(interface: &Path) -> Result<Stats, Error> {
         }
     });
 
+    let optional = |x: &'static str| async {
+        match rstats.get(x) {
+            None => Ok(None),
+            Some(x) => read_parse(x).await.map(Some),
+        }
+    };
+
     Ok(Stats {
+        tx_heartbeat_errors: optional("tx_heartbeat_errors").await?,
+        tx_carrier_errors: optional("tx_carrier_errors").await?,
+        tx_aborted_errors: optional("tx_aborted_errors").await?,
+        tx_window_errors: optional("tx_window_errors").await?,
+        rx_length_errors: optional("rx_length_errors").await?,
+        rx_missed_errors: optional("rx_missed_errors").await?,
+        rx_frame_errors: optional("rx_frame_errors").await?,
+        rx_over_errors: optional("rx_over_errors").await?,
+        rx_fifo_errors: optional("rx_fifo_errors").await?,
+        tx_fifo_errors: optional("tx_fifo_errors").await?,
+        rx_crc_errors: optional("rx_crc_errors").await?,
+        rx_compressed: optional("rx_compressed").await?,
+        tx_compressed: optional("tx_compressed").await?,
+        rx_nohandler: optional("rx_nohandler").await?,
+        collisions: optional("collisions").await?,
+        rx_dropped: optional("rx_dropped").await?,
+        tx_dropped: optional("tx_dropped").await?,
+        rx_errors: optional("rx_errors").await?,
+        tx_errors: optional("tx_errors").await?,
+        multicast: optional("multicast").await?,
         rx_packets: rx_packets.await?,
         tx_packets: tx_packets.await?,
         rx_bytes: rx_bytes.await?,
@@ -270,16 +756,19 @@ async fn get_info(interface: &PathBuf) -> Result<Info, Error> {
                 .to_string(),
             info: "speed".to_string(),
         }),
-        Some(x) => read_parse::<usize>(x).await,
+        Some(x) => read_parse::<isize>(x).await,
     };
 
+    // virtual devices are up without a
+    // speed, which the kernel shows as -1
     let speed = match (speed, &operstate) {
+        (Ok(-1), _) => 0,
         (Err(_), OperState::Up) => return Err(Error::InvalidLinkState {
             interface: interface.to_string_lossy()
                 .to_string(),
         }),
-        (Err(_), OperState::Unknown | OperState::Down) => 0,
-        (Ok(s), _) => s,
+        (Err(_), _) => 0,
+        (Ok(s), _) => s.max(0) as usize,
     };
 
     let address = match rinfo.get("address") {
@@ -291,13 +780,212 @@ async fn get_info(interface: &PathBuf) -> Result<Info, Error> {
         Some(x) => read_parse(x).await?,
     };
 
+    // carrier and duplex can't be read
+    // while the interface is down
+    let optional = |x: &'static str| {
+        let path = rinfo.get(x).cloned();
+
+        async move {
+            read_parse::<String>(&path?).await.ok()
+        }
+    };
+
+    let carrier = optional("carrier").await
+        .map(|x| x == "1");
+
     Ok(Info {
+        carrier_changes: optional("carrier_changes").await
+            .and_then(|x| x.parse().ok()),
+        duplex: optional("duplex").await
+            .and_then(|x| x.parse().ok()),
+        mtu: optional("mtu").await
+            .and_then(|x| x.parse().ok()),
+        kind: kind(interface).await,
+        addresses: Vec::new(),
         operstate,
         address,
+        carrier,
         speed,
     })
 }
 
+/// Tells the kind of an interface from
+/// its sysfs directory, like ip -d does
+async fn kind(interface: &Path) -> InterfaceType {
+    let exists = |x: &str| fs::try_exists(interface.join(x));
+
+    let read = |x: &str| {
+        let path = interface.join(x);
+
+        async move {
+            fs::read_to_string(path).await
+                .map(|x| x.trim().to_string())
+                .unwrap_or_default()
+        }
+    };
+
+    let uevent = read("uevent").await;
+
+    let devtype = uevent.lines()
+        .find_map(|x| x.strip_prefix("DEVTYPE="));
+
+    match devtype {
+        Some("bridge") => return InterfaceType::Bridge,
+        Some("bond") => return InterfaceType::Bond,
+        Some("vlan") => return InterfaceType::Vlan,
+        _ => (),
+    }
+
+    if exists("bridge").await.unwrap_or(false) {
+        return InterfaceType::Bridge;
+    }
+
+    if exists("bonding").await.unwrap_or(false) {
+        return InterfaceType::Bond;
+    }
+
+    // 772 is ARPHRD_LOOPBACK
+    if read("type").await == "772" {
+        return InterfaceType::Loopback;
+    }
+
+    if exists("tun_flags").await.unwrap_or(false) {
+        return InterfaceType::Tun;
+    }
+
+    if exists("device").await.unwrap_or(false) {
+        return InterfaceType::Physical;
+    }
+
+    // a veth links to its peer
+    // instead of to itself
+    let iflink = read("iflink").await;
+
+    if !iflink.is_empty() && iflink != read("ifindex").await {
+        return InterfaceType::Veth;
+    }
+
+    InterfaceType::Virtual
+}
+
+/// The addresses of every interface by name, from
+/// if_inet6, fib_trie and route in /proc/net.
+/// Missing files just mean no addresses of that
+/// family, e.g. when ipv6 is disabled.
+async fn addresses(proc_net: &Path) -> HashMap<String, Vec<Address>> {
+    let read = |x: &str| {
+        let path = proc_net.join(x);
+
+        async move {
+            fs::read_to_string(path).await
+                .unwrap_or_default()
+        }
+    };
+
+    let mut addresses = HashMap::<String, Vec<Address>>::new();
+
+    // address, index, prefix, scope, flags, name
+    for line in read("if_inet6").await.lines() {
+        let fields = line.split_whitespace()
+            .collect::<Vec<&str>>();
+
+        let [address, _, prefix, _, _, name] = fields[..] else {
+            continue;
+        };
+
+        let (Ok(address), Ok(prefix)) = (
+            u128::from_str_radix(address, 16),
+            u8::from_str_radix(prefix, 16),
+        ) else {
+            continue;
+        };
+
+        addresses.entry(name.to_string())
+            .or_default()
+            .push(Address {
+                address: IpAddr::V6(Ipv6Addr::from(address)),
+                prefix,
+            });
+    }
+
+    let routes = routes(&read("route").await);
+
+    for address in local(&read("fib_trie").await) {
+        // fib_trie has no interfaces, so use
+        // the most specific route to the address
+        let route = routes.iter()
+            .filter(|(_, dest, mask)| u32::from(address) & mask == *dest)
+            .max_by_key(|(_, _, mask)| mask.count_ones());
+
+        let (name, prefix) = match route {
+            Some((name, _, mask)) => (name.clone(), mask.count_ones() as u8),
+            // the loopback network is only
+            // in the local routing table
+            None if address.is_loopback() => ("lo".to_string(), 8),
+            None => continue,
+        };
+
+        addresses.entry(name)
+            .or_default()
+            .push(Address {
+                address: IpAddr::V4(address),
+                prefix,
+            });
+    }
+
+    for x in addresses.values_mut() {
+        x.sort();
+    }
+
+    addresses
+}
+
+/// The addresses marked as "/32 host LOCAL"
+/// in fib_trie, which are the ones assigned
+/// to this host's interfaces
+fn local(fib_trie: &str) -> Vec<Ipv4Addr> {
+    let mut local = Vec::new();
+    let mut leaf = None;
+
+    for line in fib_trie.lines() {
+        let line = line.trim();
+
+        if let Some(x) = line.strip_prefix("|-- ") {
+            leaf = x.parse::<Ipv4Addr>().ok();
+        } else if line.starts_with("/32 host LOCAL") {
+            local.extend(leaf);
+        }
+    }
+
+    local.sort();
+    local.dedup();
+
+    local
+}
+
+/// The interface, destination and mask of every
+/// route but the default ones, in host order
+fn routes(route: &str) -> Vec<(String, u32, u32)> {
+    route.lines().skip(1).filter_map(|x| {
+        let fields = x.split_whitespace()
+            .collect::<Vec<&str>>();
+
+        let name = fields.first()?;
+
+        // the kernel prints the network order
+        // bytes as a native endian integer
+        let hex = |x: usize| {
+            u32::from_str_radix(fields.get(x)?, 16).ok()
+                .map(|x| u32::from_be_bytes(x.to_ne_bytes()))
+        };
+
+        let (dest, mask) = (hex(1)?, hex(7)?);
+
+        (mask != 0).then(|| (name.to_string(), dest, mask))
+    })
+    .collect()
+}
+
 async fn read_parse<T>(
     path: &PathBuf
 ) -> Result<T, Error>
@@ -0,0 +1,227 @@
+use std::{
+    num::{ParseFloatError, ParseIntError},
+    path::PathBuf,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use monitor::probe::Probe;
+use thiserror::Error;
+
+use super::Source;
+
+use tokio::{
+    fs,
+    io,
+};
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// The share of time tasks were stalled on a
+/// resource, averaged over 10, 60 and 300 seconds,
+/// and the total stall time in microseconds
+pub struct Stall {
+    pub avg300: f64,
+    pub avg60: f64,
+    pub avg10: f64,
+    pub total: u64,
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// The pressure on a single resource
+///
+/// some is when at least one task is stalled,
+/// full is when every non-idle task is. Not every
+/// resource reports both, irq only has full.
+pub struct Resource {
+    pub some: Option<Stall>,
+    pub full: Option<Stall>,
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// Pressure stall information (PSI) from the
+/// /proc/pressure directory
+///
+/// Kernels built without PSI, or booted with
+/// psi=0, have none of the files, in which case
+/// nothing is available rather than an error.
+/// irq pressure also needs a newer kernel.
+pub struct Pressure {
+    pub memory: Option<Resource>,
+    pub cpu: Option<Resource>,
+    pub irq: Option<Resource>,
+    pub io: Option<Resource>,
+    pub available: bool,
+}
+
+#[derive(
+    Error,
+    Debug,
+)]
+
+pub enum Error {
+    #[error("io error occurred getting pressure: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid pressure line: {0}")]
+    InvalidLine(String),
+    #[error("missing field '{0}' from pressure")]
+    MissingField(String),
+    #[error("failed to parse float: {0}")]
+    ParseFloat(ParseFloatError),
+    #[error("failed to parse int: {0}")]
+    ParseInt(ParseIntError),
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Pressure`] from
+/// the /proc/pressure directory
+pub struct PressureProbe {
+    pub dir: PathBuf,
+}
+
+impl PressureProbe {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+        }
+    }
+
+    async fn resource(&self, name: &str) -> Result<Option<Resource>, Error> {
+        let pressure = match fs::read_to_string(self.dir.join(name)).await {
+            Ok(x) => x,
+            // without PSI the files are either
+            // missing or refuse to be read
+            Err(e) if e.kind() == io::ErrorKind::NotFound
+                || e.kind() == io::ErrorKind::Unsupported
+                || e.raw_os_error() == Some(libc::EOPNOTSUPP) =>
+            {
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut resource = Resource::default();
+
+        for line in pressure.lines() {
+            let Some((kind, stall)) = line.split_once(' ') else {
+                return Err(Error::InvalidLine(line.to_string()));
+            };
+
+            let stall = parse_stall(stall)?;
+
+            match kind {
+                "some" => resource.some = Some(stall),
+                "full" => resource.full = Some(stall),
+                _ => return Err(Error::InvalidLine(
+                    line.to_string()
+                )),
+            }
+        }
+
+        Ok(Some(resource))
+    }
+}
+
+impl From<&Source> for PressureProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.proc("PRESSURE", "pressure"))
+    }
+}
+
+impl Default for PressureProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for PressureProbe {
+    type Output = Pressure;
+
+    type Error = Error;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let memory = self.resource("memory").await?;
+        let cpu = self.resource("cpu").await?;
+        let irq = self.resource("irq").await?;
+        let io = self.resource("io").await?;
+
+        let available = [memory, cpu, irq, io]
+            .iter()
+            .any(Option::is_some);
+
+        Ok(Pressure {
+            available,
+            memory,
+            cpu,
+            irq,
+            io,
+        })
+    }
+}
+
+/// Parses the key=value pairs
+/// after some / full
+fn parse_stall(stall: &str) -> Result<Stall, Error> {
+    let field = |key: &str| {
+        stall.split_whitespace()
+            .find_map(|x| x.strip_prefix(key)?.strip_prefix('='))
+            .ok_or(Error::MissingField(key.to_string()))
+    };
+
+    let [
+        avg300,
+        avg60,
+        avg10,
+    ] = [
+        "avg300",
+        "avg60",
+        "avg10",
+    ].try_map(|x| field(x)?.parse::<f64>()
+        .map_err(Error::ParseFloat))?;
+
+    let total = field("total")?.parse::<u64>()
+        .map_err(Error::ParseInt)?;
+
+    Ok(Stall {
+        avg300,
+        avg60,
+        avg10,
+        total,
+    })
+}
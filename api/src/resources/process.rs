@@ -0,0 +1,865 @@
+use std::{
+    collections::{HashMap, HashSet},
+    num::ParseIntError,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use monitor::{
+    probe::Probe,
+    rate::{CounterReset, Counters, RateProbe},
+};
+use thiserror::Error;
+
+use super::{
+    cpu::{CoresUsageProbe, UsageError, UsageProbe},
+    memory::{self, MemoryProbe},
+    Source,
+};
+
+use tokio::{
+    fs::{self, read_dir},
+    io,
+};
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+#[serde(rename_all = "lowercase")]
+pub enum State {
+    Running,
+    Sleeping,
+    DiskSleep,
+    Zombie,
+    Stopped,
+    TracingStop,
+    Dead,
+    Idle,
+    #[default]
+    Unknown,
+}
+
+impl From<char> for State {
+    fn from(value: char) -> Self {
+        match value {
+            'R' => Self::Running,
+            'S' => Self::Sleeping,
+            'D' => Self::DiskSleep,
+            'Z' => Self::Zombie,
+            'T' => Self::Stopped,
+            't' => Self::TracingStop,
+            'X' | 'x' => Self::Dead,
+            'I' => Self::Idle,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// A single process, from the stat, status
+/// and cmdline files of /proc/[pid]
+///
+/// utime, stime and start_time are in clock
+/// ticks, cpu_time is utime + stime as a
+/// duration. rss is in bytes.
+pub struct Process {
+    pub user: Option<String>,
+    pub cmdline: Vec<String>,
+    pub cpu_time: Duration,
+    pub start_time: u64,
+    pub threads: u64,
+    pub state: State,
+    pub name: String,
+    pub utime: u64,
+    pub stime: u64,
+    pub ppid: u32,
+    pub rss: u64,
+    pub pid: u32,
+    pub uid: u32,
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Every process running on the system
+pub struct Processes {
+    pub processes: Vec<Process>,
+}
+
+#[derive(
+    Error,
+    Debug,
+)]
+
+pub enum Error {
+    #[error("io error occurred getting processes: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid stat for process {0}")]
+    InvalidStat(u32),
+    #[error("field '{field}' missing for process {pid}")]
+    MissingField {
+        field: String,
+        pid: u32,
+    },
+    #[error("failed to parse int: {0}")]
+    ParseInt(#[from] ParseIntError),
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Processes`] from every
+/// /proc/[pid] directory, using the passwd
+/// file to resolve the user names
+pub struct ProcessesProbe {
+    pub passwd: PathBuf,
+    pub dir: PathBuf,
+}
+
+impl ProcessesProbe {
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        passwd: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            passwd: passwd.into(),
+            dir: dir.into(),
+        }
+    }
+
+    /// The processes, without resolving
+    /// the user names
+    pub(crate) async fn read(&self) -> Result<Vec<Process>, Error> {
+        let mut rdir = read_dir(&self.dir).await?;
+
+        let ticks = ticks();
+
+        let mut processes = Vec::new();
+
+        while let Some(ent) = rdir.next_entry().await? {
+            let Ok(pid) = ent.file_name()
+                .to_string_lossy()
+                .parse::<u32>() else
+            {
+                continue;
+            };
+
+            match process(&ent.path(), pid, ticks).await {
+                Ok(x) => processes.push(x),
+                // the process exited while
+                // it was being read
+                Err(Error::Io(e)) if vanished(&e) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        processes.sort_by_key(|x| x.pid);
+
+        Ok(processes)
+    }
+}
+
+impl From<&Source> for ProcessesProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(&source.procfs, &source.passwd)
+    }
+}
+
+impl Default for ProcessesProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for ProcessesProbe {
+    type Output = Processes;
+
+    type Error = Error;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let mut processes = self.read().await?;
+
+        let users = users(&self.passwd).await;
+
+        for x in processes.iter_mut() {
+            x.user = users.get(&x.uid).cloned();
+        }
+
+        Ok(Processes {
+            processes,
+        })
+    }
+}
+
+/// The amount of clock ticks per second
+/// the cpu times in /proc are reported in
+pub(crate) fn ticks() -> u64 {
+    // SAFETY: sysconf has no preconditions
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        x if x > 0 => x as u64,
+        _ => 100,
+    }
+}
+
+fn vanished(e: &io::Error) -> bool {
+    e.kind() == io::ErrorKind::NotFound
+        || e.raw_os_error() == Some(libc::ESRCH)
+}
+
+async fn process(dir: &Path, pid: u32, ticks: u64) -> Result<Process, Error> {
+    let stat = fs::read_to_string(dir.join("stat")).await?;
+    let status = fs::read_to_string(dir.join("status")).await?;
+    let cmdline = fs::read(dir.join("cmdline")).await?;
+
+    // the name is in parentheses and may
+    // contain spaces or parentheses itself
+    let (name, stat) = stat.split_once('(')
+        .and_then(|(_, x)| x.rsplit_once(')'))
+        .ok_or(Error::InvalidStat(pid))?;
+
+    let stat = stat.split_whitespace()
+        .collect::<Vec<&str>>();
+
+    let field = |x: usize| {
+        stat.get(x).copied()
+            .ok_or(Error::InvalidStat(pid))
+    };
+
+    let state = field(0)?.chars().next()
+        .map(State::from)
+        .unwrap_or_default();
+
+    let ppid = field(1)?.parse::<u32>()?;
+    let utime = field(11)?.parse::<u64>()?;
+    let stime = field(12)?.parse::<u64>()?;
+    let threads = field(17)?.parse::<u64>()?;
+    let start_time = field(19)?.parse::<u64>()?;
+
+    let status = status.lines()
+        .filter_map(|x| x.split_once(':'))
+        .map(|(k, v)| (k.trim(), v.trim()))
+        .collect::<HashMap<&str, &str>>();
+
+    let uid = status.get("Uid")
+        .and_then(|x| x.split_whitespace().next())
+        .ok_or(Error::MissingField {
+            field: "Uid".to_string(),
+            pid,
+        })?
+        .parse::<u32>()?;
+
+    // kernel threads have no memory
+    let rss = match status.get("VmRSS") {
+        Some(x) => x.trim_end_matches("kB")
+            .trim()
+            .parse::<u64>()? * 1024,
+        None => 0,
+    };
+
+    let cmdline = cmdline.split(|x| *x == 0)
+        .filter(|x| !x.is_empty())
+        .map(|x| String::from_utf8_lossy(x).to_string())
+        .collect();
+
+    let cpu_time = Duration::from_secs_f64(
+        (utime + stime) as f64 / ticks as f64
+    );
+
+    Ok(Process {
+        name: name.to_string(),
+        user: None,
+        start_time,
+        cpu_time,
+        cmdline,
+        threads,
+        state,
+        utime,
+        stime,
+        ppid,
+        rss,
+        pid,
+        uid,
+    })
+}
+
+/// The user names by uid, unreadable
+/// passwd files just resolve nothing
+async fn users(passwd: &Path) -> HashMap<u32, String> {
+    let Ok(passwd) = fs::read_to_string(passwd).await else {
+        return HashMap::new();
+    };
+
+    passwd.lines().filter_map(|x| {
+        let mut parts = x.split(':');
+
+        let name = parts.next()?;
+        let uid = parts.nth(1)?.parse::<u32>().ok()?;
+
+        Some((uid, name.to_string()))
+    })
+    .collect()
+}
+
+#[derive(
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+#[serde(rename_all = "snake_case")]
+pub enum SortBy {
+    #[default]
+    Pid,
+    Ppid,
+    Name,
+    User,
+    Rss,
+    Threads,
+    CpuTime,
+}
+
+#[derive(
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+#[serde(rename_all = "lowercase")]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+#[derive(
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Sorting, filtering and limiting of [`Processes`]
+///
+/// Without an order, names are sorted ascending
+/// and amounts (rss, threads, cpu_time) descending,
+/// so n gives the top processes.
+pub struct Query {
+    pub order: Option<Order>,
+    pub sort: Option<SortBy>,
+    pub user: Option<String>,
+    pub name: Option<String>,
+    pub n: Option<usize>,
+}
+
+impl Processes {
+    pub fn query(mut self, query: &Query) -> Self {
+        if let Some(name) = &query.name {
+            self.processes.retain(|x| x.name.contains(name.as_str()));
+        }
+
+        if let Some(user) = &query.user {
+            self.processes.retain(|x| {
+                x.user.as_ref() == Some(user) || x.uid.to_string() == *user
+            });
+        }
+
+        let sort = query.sort.unwrap_or_default();
+
+        let order = query.order.unwrap_or(match sort {
+            SortBy::Rss | SortBy::Threads | SortBy::CpuTime => Order::Desc,
+            _ => Order::Asc,
+        });
+
+        self.processes.sort_by(|a, b| {
+            let ord = match sort {
+                SortBy::Pid => a.pid.cmp(&b.pid),
+                SortBy::Ppid => a.ppid.cmp(&b.ppid),
+                SortBy::Name => a.name.cmp(&b.name),
+                SortBy::User => a.user.cmp(&b.user),
+                SortBy::Rss => a.rss.cmp(&b.rss),
+                SortBy::Threads => a.threads.cmp(&b.threads),
+                SortBy::CpuTime => a.cpu_time.cmp(&b.cpu_time),
+            };
+
+            match order {
+                Order::Asc => ord,
+                Order::Desc => ord.reverse(),
+            }
+        });
+
+        if let Some(n) = query.n {
+            self.processes.truncate(n);
+        }
+
+        self
+    }
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// A [`Process`] with its share of the cpu
+/// since the previous probe and of the memory
+///
+/// cpu_percent is relative to a single cpu,
+/// like top, so it goes up to 100 times the
+/// amount of logical cpus
+pub struct TopProcess {
+    #[serde(flatten)]
+    pub process: Process,
+    pub cpu_percent: f64,
+    pub mem_percent: f64,
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Every process, by cpu usage
+pub struct Top {
+    pub processes: Vec<TopProcess>,
+}
+
+#[derive(
+    Error,
+    Debug,
+)]
+
+pub enum TopError {
+    #[error("failed to get processes: {0}")]
+    Processes(#[from] Error),
+    #[error("failed to get cpu usage: {0}")]
+    Usage(#[from] UsageError),
+    #[error("failed to get memory: {0}")]
+    Memory(#[from] memory::Error),
+    #[error("cpu time {0}")]
+    CounterReset(#[from] CounterReset),
+}
+
+#[derive(
+    Debug,
+    Clone,
+)]
+
+/// Every process, along with the totals
+/// their share of the cpu and memory is
+/// taken from
+pub struct Snapshot {
+    pub processes: Processes,
+    /// All the jiffies spent by every cpu
+    pub total: u64,
+    pub cpus: usize,
+    /// In bytes
+    pub memory: u64,
+}
+
+#[derive(
+    Default,
+    Debug,
+)]
+
+/// Probes a [`Snapshot`] of the processes,
+/// for diffing their utime and stime
+/// against the /proc/stat totals
+pub struct SnapshotProbe {
+    pub processes: ProcessesProbe,
+    pub cores: CoresUsageProbe,
+    pub memory: MemoryProbe,
+    pub usage: UsageProbe,
+}
+
+impl SnapshotProbe {
+    pub fn new(
+        processes: ProcessesProbe,
+        cores: CoresUsageProbe,
+        memory: MemoryProbe,
+        usage: UsageProbe,
+    ) -> Self {
+        Self {
+            processes,
+            memory,
+            cores,
+            usage,
+        }
+    }
+}
+
+impl From<&Source> for SnapshotProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(
+            source.into(),
+            source.into(),
+            source.into(),
+            source.into(),
+        )
+    }
+}
+
+impl Probe for SnapshotProbe {
+    type Output = Snapshot;
+
+    type Error = TopError;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let processes = self.processes.probe().await?;
+        let total = self.usage.probe().await?.total();
+
+        let cpus = self.cores.probe().await?
+            .len()
+            .max(1);
+
+        let memory = self.memory.probe().await?.total * 1024;
+
+        Ok(Snapshot {
+            processes,
+            memory,
+            total,
+            cpus,
+        })
+    }
+}
+
+impl Counters for SnapshotProbe {
+    fn rate_since(now: &Snapshot, prev: &Snapshot, _: Duration) -> Option<Top> {
+        // keyed by pid and start time so a reused
+        // pid is not diffed against another process
+        let times = prev.processes.processes.iter()
+            .map(|x| ((x.pid, x.start_time), x.utime + x.stime))
+            .collect::<HashMap<(u32, u64), u64>>();
+
+        // counters going backwards were
+        // reset, so show no usage instead
+        let elapsed = now.total.saturating_sub(prev.total);
+
+        let processes = now.processes.processes.iter().map(|x| {
+            let time = x.utime + x.stime;
+
+            // processes started since the previous
+            // probe spent all their time since then
+            let since = times.get(&(x.pid, x.start_time))
+                .copied()
+                .unwrap_or(0);
+
+            let cpu_percent = match elapsed {
+                0 => 0.0,
+                elapsed => time.saturating_sub(since) as f64
+                    / elapsed as f64
+                    * 100.0
+                    * now.cpus as f64,
+            };
+
+            let mem_percent = match now.memory {
+                0 => 0.0,
+                memory => x.rss as f64 / memory as f64 * 100.0,
+            };
+
+            TopProcess {
+                process: x.clone(),
+                cpu_percent,
+                mem_percent,
+            }
+        })
+        .collect();
+
+        Some(Top {
+            processes,
+        }
+        .query(&TopQuery::default()))
+    }
+
+    type Rate = Top;
+}
+
+/// Probes the [`Top`] processes
+/// since the previous probe
+pub type TopProbe = RateProbe<SnapshotProbe>;
+
+#[derive(
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+#[serde(rename_all = "lowercase")]
+pub enum TopBy {
+    #[default]
+    Cpu,
+    #[serde(alias = "mem")]
+    Memory,
+}
+
+#[derive(
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Ranking of the [`Top`] processes, always
+/// descending, ties are broken by pid
+pub struct TopQuery {
+    pub by: Option<TopBy>,
+    pub n: Option<usize>,
+}
+
+impl Top {
+    pub fn query(mut self, query: &TopQuery) -> Self {
+        let by = query.by.unwrap_or_default();
+
+        self.processes.sort_by(|a, b| {
+            let ord = match by {
+                TopBy::Cpu => b.cpu_percent.total_cmp(&a.cpu_percent),
+                TopBy::Memory => b.mem_percent.total_cmp(&a.mem_percent),
+            };
+
+            ord.then(a.process.pid.cmp(&b.process.pid))
+        });
+
+        if let Some(n) = query.n {
+            self.processes.truncate(n);
+        }
+
+        self
+    }
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// The usage of a process and
+/// all of its descendants
+pub struct Subtree {
+    pub cpu_percent: f64,
+    pub mem_percent: f64,
+    pub processes: usize,
+    pub rss: u64,
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// A process in the [`Tree`], with
+/// its children sorted by pid
+pub struct Node {
+    #[serde(flatten)]
+    pub process: TopProcess,
+    pub subtree: Subtree,
+    pub children: Vec<Node>,
+}
+
+#[derive(
+    Deserialize,
+    PartialOrd,
+    Serialize,
+    PartialEq,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// The [`Top`] processes by parent, roots
+/// are the processes without a known parent
+/// (init, kthreadd, or ones outside the pid
+/// namespace), and the lowest pid of any
+/// ppid cycle
+pub struct Tree {
+    pub roots: Vec<Node>,
+}
+
+impl From<Top> for Tree {
+    fn from(top: Top) -> Self {
+        let mut processes = top.processes.into_iter()
+            .map(|x| (x.process.pid, x))
+            .collect::<HashMap<u32, TopProcess>>();
+
+        let mut roots = Vec::new();
+        let mut children = HashMap::<u32, Vec<u32>>::new();
+
+        for x in processes.values() {
+            let (pid, ppid) = (x.process.pid, x.process.ppid);
+
+            if ppid == pid || !processes.contains_key(&ppid) {
+                roots.push(pid);
+            } else {
+                children.entry(ppid)
+                    .or_default()
+                    .push(pid);
+            }
+        }
+
+        roots.sort();
+
+        for x in children.values_mut() {
+            x.sort();
+        }
+
+        // walks down from every root, parents
+        // before children, keeping the edges
+        // taken so every process shows up once
+        let mut seen = HashSet::new();
+        let mut order = Vec::new();
+        let mut edges = HashMap::<u32, Vec<u32>>::new();
+
+        let mut tops = Vec::new();
+
+        loop {
+            for root in roots {
+                if !seen.insert(root) {
+                    continue;
+                }
+
+                tops.push(root);
+
+                let mut stack = vec![root];
+
+                while let Some(pid) = stack.pop() {
+                    order.push(pid);
+
+                    for &x in children.get(&pid).into_iter().flatten() {
+                        if seen.insert(x) {
+                            edges.entry(pid)
+                                .or_default()
+                                .push(x);
+
+                            stack.push(x);
+                        }
+                    }
+                }
+            }
+
+            // whatever's left can't be reached from a
+            // root, so it's in a ppid cycle or hangs
+            // off one, which is broken at its lowest pid
+            let left = processes.keys()
+                .filter(|x| !seen.contains(x))
+                .min();
+
+            match left {
+                Some(&pid) => roots = vec![pid],
+                None => break,
+            }
+        }
+
+        // children before parents, so every
+        // subtree is done before it's needed
+        let mut nodes = HashMap::<u32, Node>::new();
+
+        for pid in order.into_iter().rev() {
+            let Some(process) = processes.remove(&pid) else {
+                continue;
+            };
+
+            let children = edges.remove(&pid)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|x| nodes.remove(&x))
+                .collect::<Vec<Node>>();
+
+            nodes.insert(pid, node(process, children));
+        }
+
+        tops.sort();
+
+        let roots = tops.into_iter()
+            .filter_map(|x| nodes.remove(&x))
+            .collect();
+
+        Self {
+            roots,
+        }
+    }
+}
+
+fn node(process: TopProcess, children: Vec<Node>) -> Node {
+    let subtree = children.iter().fold(
+        Subtree {
+            cpu_percent: process.cpu_percent,
+            mem_percent: process.mem_percent,
+            rss: process.process.rss,
+            processes: 1,
+        },
+        |acc, x| Subtree {
+            cpu_percent: acc.cpu_percent + x.subtree.cpu_percent,
+            mem_percent: acc.mem_percent + x.subtree.mem_percent,
+            processes: acc.processes + x.subtree.processes,
+            rss: acc.rss + x.subtree.rss,
+        },
+    );
+
+    Node {
+        children,
+        subtree,
+        process,
+    }
+}
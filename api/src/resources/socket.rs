@@ -0,0 +1,380 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    num::ParseIntError,
+    path::{Path, PathBuf},
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use monitor::probe::Probe;
+use thiserror::Error;
+
+use tokio::{
+    fs,
+    io,
+};
+
+use super::Source;
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+}
+
+impl Protocol {
+    pub fn is_tcp(&self) -> bool {
+        matches!(self, Self::Tcp | Self::Tcp6)
+    }
+
+    fn file(&self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp",
+            Self::Tcp6 => "tcp6",
+            Self::Udp => "udp",
+            Self::Udp6 => "udp6",
+        }
+    }
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// The kernel's tcp states, udp sockets
+/// only use established and close
+#[serde(rename_all = "snake_case")]
+pub enum TcpState {
+    Established,
+    SynSent,
+    SynRecv,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    Close,
+    CloseWait,
+    LastAck,
+    Listen,
+    Closing,
+    NewSynRecv,
+    Unknown,
+}
+
+impl From<u8> for TcpState {
+    fn from(value: u8) -> Self {
+        match value {
+            0x01 => Self::Established,
+            0x02 => Self::SynSent,
+            0x03 => Self::SynRecv,
+            0x04 => Self::FinWait1,
+            0x05 => Self::FinWait2,
+            0x06 => Self::TimeWait,
+            0x07 => Self::Close,
+            0x08 => Self::CloseWait,
+            0x09 => Self::LastAck,
+            0x0A => Self::Listen,
+            0x0B => Self::Closing,
+            0x0C => Self::NewSynRecv,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl Display for TcpState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Established => write!(f, "established"),
+            Self::SynSent => write!(f, "syn_sent"),
+            Self::SynRecv => write!(f, "syn_recv"),
+            Self::FinWait1 => write!(f, "fin_wait1"),
+            Self::FinWait2 => write!(f, "fin_wait2"),
+            Self::TimeWait => write!(f, "time_wait"),
+            Self::Close => write!(f, "close"),
+            Self::CloseWait => write!(f, "close_wait"),
+            Self::LastAck => write!(f, "last_ack"),
+            Self::Listen => write!(f, "listen"),
+            Self::Closing => write!(f, "closing"),
+            Self::NewSynRecv => write!(f, "new_syn_recv"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+)]
+
+/// A single socket, from its line
+/// in /proc/net/{tcp,tcp6,udp,udp6}
+pub struct Socket {
+    pub protocol: Protocol,
+    pub remote: SocketAddr,
+    pub local: SocketAddr,
+    pub state: TcpState,
+    pub rx_queue: u64,
+    pub tx_queue: u64,
+    pub inode: u64,
+    pub uid: u32,
+}
+
+impl Socket {
+    /// Listening tcp sockets, and udp
+    /// sockets that aren't connected
+    pub fn is_listening(&self) -> bool {
+        match self.protocol.is_tcp() {
+            true => self.state == TcpState::Listen,
+            false => self.remote.port() == 0,
+        }
+    }
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+)]
+
+/// A local address a socket is listening on
+pub struct Listener {
+    pub protocol: Protocol,
+    pub address: SocketAddr,
+}
+
+#[derive(
+    PartialOrd, Ord,
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// A summary of every tcp and udp socket
+///
+/// states counts the tcp sockets by state, a
+/// growing close_wait usually means a service
+/// isn't closing its connections. sockets is
+/// the full table, only kept when asked for.
+pub struct Sockets {
+    pub states: BTreeMap<TcpState, usize>,
+    pub sockets: Option<Vec<Socket>>,
+    pub listening: Vec<Listener>,
+    pub tcp: usize,
+    pub udp: usize,
+}
+
+#[derive(
+    Error,
+    Debug,
+)]
+
+pub enum Error {
+    #[error("io error occurred getting sockets: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid socket line: {0}")]
+    InvalidLine(String),
+    #[error("failed to parse int: {0}")]
+    ParseInt(#[from] ParseIntError),
+}
+
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Sockets`] from the
+/// socket tables in /proc/net
+pub struct SocketsProbe {
+    pub dir: PathBuf,
+}
+
+impl SocketsProbe {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+        }
+    }
+}
+
+impl From<&Source> for SocketsProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.proc("PROC_NET", "net"))
+    }
+}
+
+impl Default for SocketsProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for SocketsProbe {
+    type Output = Sockets;
+
+    type Error = Error;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let mut sockets = Vec::new();
+
+        for protocol in [
+            Protocol::Tcp,
+            Protocol::Tcp6,
+            Protocol::Udp,
+            Protocol::Udp6,
+        ] {
+            sockets.extend(table(&self.dir.join(protocol.file()), protocol).await?);
+        }
+
+        let mut states = BTreeMap::new();
+
+        for x in sockets.iter().filter(|x| x.protocol.is_tcp()) {
+            *states.entry(x.state).or_default() += 1;
+        }
+
+        let mut listening = sockets.iter()
+            .filter(|x| x.is_listening())
+            .map(|x| Listener {
+                protocol: x.protocol,
+                address: x.local,
+            })
+            .collect::<Vec<Listener>>();
+
+        listening.sort();
+        listening.dedup();
+
+        let tcp = sockets.iter()
+            .filter(|x| x.protocol.is_tcp())
+            .count();
+
+        Ok(Sockets {
+            udp: sockets.len() - tcp,
+            sockets: Some(sockets),
+            listening,
+            states,
+            tcp,
+        })
+    }
+}
+
+/// The sockets in one table, a missing
+/// table (e.g. no ipv6) has none
+async fn table(path: &Path, protocol: Protocol) -> Result<Vec<Socket>, Error> {
+    let table = match fs::read_to_string(path).await {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Ok(Vec::new());
+        }
+        x => x?,
+    };
+
+    table.lines()
+        .skip(1)
+        .filter(|x| !x.trim().is_empty())
+        .map(|x| socket(x, protocol))
+        .try_collect()
+}
+
+fn socket(line: &str, protocol: Protocol) -> Result<Socket, Error> {
+    let invalid = || Error::InvalidLine(line.to_string());
+
+    let fields = line.split_whitespace()
+        .collect::<Vec<&str>>();
+
+    let [_, local, remote, state, queues, _, _, uid, _, inode, ..] = fields[..] else {
+        return Err(invalid());
+    };
+
+    let (tx_queue, rx_queue) = queues.split_once(':')
+        .ok_or_else(invalid)?;
+
+    Ok(Socket {
+        local: address(local).ok_or_else(invalid)?,
+        remote: address(remote).ok_or_else(invalid)?,
+        state: u8::from_str_radix(state, 16)?.into(),
+        tx_queue: u64::from_str_radix(tx_queue, 16)?,
+        rx_queue: u64::from_str_radix(rx_queue, 16)?,
+        inode: inode.parse()?,
+        uid: uid.parse()?,
+        protocol,
+    })
+}
+
+/// Parses an address like 0100007F:0035, the
+/// ip is in native endian 32 bit words
+fn address(field: &str) -> Option<SocketAddr> {
+    let (ip, port) = field.split_once(':')?;
+
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    let words = (0..ip.len() / 8)
+        .map(|x| ip.get(x * 8..x * 8 + 8))
+        .map(|x| u32::from_str_radix(x?, 16).ok())
+        .collect::<Option<Vec<u32>>>()?;
+
+    let bytes = words.iter()
+        .flat_map(|x| x.to_ne_bytes())
+        .collect::<Vec<u8>>();
+
+    let ip = match bytes.len() {
+        4 => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => return None,
+    };
+
+    Some(SocketAddr::new(ip, port))
+}
+
+#[derive(
+    Deserialize,
+    Serialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Whether to keep the full socket table
+pub struct Query {
+    #[serde(default)]
+    pub table: bool,
+}
+
+impl Sockets {
+    pub fn query(mut self, query: &Query) -> Self {
+        if !query.table {
+            self.sockets = None;
+        }
+
+        self
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     num::ParseFloatError,
+    path::PathBuf,
     time::Duration,
 };
 
@@ -11,6 +12,8 @@ use serde::{
 use monitor::probe::Probe;
 use thiserror::Error;
 
+use super::Source;
+
 use tokio::{
     io::{
         AsyncBufReadExt,
@@ -56,16 +59,44 @@ pub enum Error {
     Empty,
 }
 
-impl Probe for Uptime {
+#[derive(
+    Clone,
+    Debug,
+)]
+
+/// Probes the [`Uptime`] from
+/// the /proc/uptime file
+pub struct UptimeProbe {
+    pub file: PathBuf,
+}
+
+impl UptimeProbe {
+    pub fn new(file: impl Into<PathBuf>) -> Self {
+        Self {
+            file: file.into(),
+        }
+    }
+}
+
+impl From<&Source> for UptimeProbe {
+    fn from(source: &Source) -> Self {
+        Self::new(source.proc("UPTIME", "uptime"))
+    }
+}
+
+impl Default for UptimeProbe {
+    fn default() -> Self {
+        Self::from(&Source::default())
+    }
+}
+
+impl Probe for UptimeProbe {
     type Output = Uptime;
 
     type Error = Error;
 
-    async fn probe() -> Result<Self::Output, Self::Error> {
-        let uptime_file = std::env::var("UPTIME")
-            .unwrap_or("/proc/uptime".into());
-
-        let file = File::open(uptime_file).await?;
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let file = File::open(&self.file).await?;
 
         let mut uptime = String::new();
 
@@ -91,7 +122,7 @@ impl Probe for Uptime {
             .try_into()
             .map_err(|_| Error::InvalidUptime)?;
 
-        Ok(Self {
+        Ok(Uptime {
             uptime,
             idle,
         })
@@ -1,4 +1,5 @@
 use axum::{
+    extract::State,
     response::{
         sse::Event,
         Sse,
@@ -13,11 +14,51 @@ use monitor::probe::Probe;
 use futures_util::Stream;
 
 use crate::{
-    error::ApiError, resources::cpu::{Core, Cores, Cpu, Info, Usage}
+    error::ApiError,
+    resources::cpu::{
+        Core, CorePercent, CoreUsage, Cpu, Info, Thermal, Usage, UsagePercent,
+    },
+    state::Hubs,
 };
 
-pub async fn cpu_cores_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let s = Cores::stream().map(|x| {
+pub async fn cpu_thermal_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.cores.stream().map(|x| {
+        Event::default().json_data(x)
+    })
+    .filter_map(|x| x.map(Ok).ok());
+
+    Sse::new(s)
+}
+
+pub async fn cpu_cores_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.cores.stream().map(|x| {
+        Event::default().json_data(x.cores)
+    })
+    .filter_map(|x| x.map(Ok).ok());
+
+    Sse::new(s)
+}
+
+pub async fn cpu_usage_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.usage.stream().map(|x| {
+        Event::default().json_data(x)
+    })
+    .filter_map(|x| x.map(Ok).ok());
+
+    Sse::new(s)
+}
+
+pub async fn cpu_usage_percent_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.usage_percent.stream().map(|x| {
+        Event::default().json_data(x)
+    })
+    .filter_map(|x| x.map(Ok).ok());
+
+    Sse::new(s)
+}
+
+pub async fn cpu_usage_cores_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.cores_usage.stream().map(|x| {
         Event::default().json_data(x)
     })
     .filter_map(|x| x.map(Ok).ok());
@@ -25,8 +66,8 @@ pub async fn cpu_cores_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>
     Sse::new(s)
 }
 
-pub async fn cpu_usage_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let s = Usage::stream().map(|x| {
+pub async fn cpu_usage_cores_percent_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.cores_percent.stream().map(|x| {
         Event::default().json_data(x)
     })
     .filter_map(|x| x.map(Ok).ok());
@@ -34,8 +75,8 @@ pub async fn cpu_usage_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>
     Sse::new(s)
 }
 
-pub async fn cpu_info_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let s = Info::stream().map(|x| {
+pub async fn cpu_info_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.info.stream().map(|x| {
         Event::default().json_data(x)
     })
     .filter_map(|x| x.map(Ok).ok());
@@ -43,8 +84,8 @@ pub async fn cpu_info_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>
     Sse::new(s)
 }
 
-pub async fn cpu_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let s = Cpu::stream().map(|x| {
+pub async fn cpu_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.cpu.stream().map(|x| {
         Event::default().json_data(x)
     })
     .filter_map(|x| x.map(Ok).ok());
@@ -52,18 +93,34 @@ pub async fn cpu_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     Sse::new(s)
 }
 
-pub async fn cpu_cores() -> Result<Json<Vec<Core>>, ApiError> {
-    Ok(Cores::probe().await.map(Json)?)
+pub async fn cpu_thermal(State(hubs): State<Hubs>) -> Result<Json<Thermal>, ApiError> {
+    Ok(hubs.cores.probe().probe().await.map(Json)?)
+}
+
+pub async fn cpu_cores(State(hubs): State<Hubs>) -> Result<Json<Vec<Core>>, ApiError> {
+    Ok(hubs.cores.probe().probe().await.map(|x| Json(x.cores))?)
+}
+
+pub async fn cpu_usage(State(hubs): State<Hubs>) -> Result<Json<Usage>, ApiError> {
+    Ok(hubs.usage.probe().probe().await.map(Json)?)
+}
+
+pub async fn cpu_usage_percent(State(hubs): State<Hubs>) -> Result<Json<UsagePercent>, ApiError> {
+    Ok(hubs.usage_percent.probe().measure().await.map(Json)?)
+}
+
+pub async fn cpu_usage_cores(State(hubs): State<Hubs>) -> Result<Json<Vec<CoreUsage>>, ApiError> {
+    Ok(hubs.cores_usage.probe().probe().await.map(Json)?)
 }
 
-pub async fn cpu_usage() -> Result<Json<Usage>, ApiError> {
-    Ok(Usage::probe().await.map(Json)?)
+pub async fn cpu_usage_cores_percent(State(hubs): State<Hubs>) -> Result<Json<Vec<CorePercent>>, ApiError> {
+    Ok(hubs.cores_percent.probe().measure().await.map(Json)?)
 }
 
-pub async fn cpu_info() -> Result<Json<Info>, ApiError> {
-    Ok(Info::probe().await.map(Json)?)
+pub async fn cpu_info(State(hubs): State<Hubs>) -> Result<Json<Info>, ApiError> {
+    Ok(hubs.info.probe().probe().await.map(Json)?)
 }
 
-pub async fn cpu() -> Result<Json<Cpu>, ApiError> {
-    Ok(Cpu::probe().await.map(Json)?)
+pub async fn cpu(State(hubs): State<Hubs>) -> Result<Json<Cpu>, ApiError> {
+    Ok(hubs.cpu.probe().probe().await.map(Json)?)
 }
@@ -0,0 +1,50 @@
+use axum::{
+    extract::{Query, State},
+    response::{
+        sse::Event,
+        IntoResponse,
+        Response,
+        Sse,
+    },
+    Json,
+};
+
+use std::convert::Infallible;
+
+use tokio_stream::StreamExt;
+use monitor::probe::Probe;
+use futures_util::{future::Either, Stream};
+
+use crate::{
+    error::ApiError,
+    state::Hubs,
+};
+
+use super::RateQuery;
+
+pub async fn disks_sse(
+    State(hubs): State<Hubs>,
+    Query(query): Query<RateQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = match query.rate {
+        true => Either::Left(hubs.disk_rates.stream().map(|x| {
+            Event::default().json_data(x)
+        })),
+        false => Either::Right(hubs.disks.stream().map(|x| {
+            Event::default().json_data(x)
+        })),
+    }
+    .filter_map(|x| x.map(Ok).ok());
+
+    Sse::new(s)
+}
+
+pub async fn disks(
+    State(hubs): State<Hubs>,
+    Query(query): Query<RateQuery>,
+) -> Result<Response, ApiError> {
+    Ok(match query.rate {
+        true => Json(hubs.disk_rates.probe().measure().await?).into_response(),
+        false => Json(hubs.disks.probe().probe().await?).into_response(),
+    })
+}
@@ -0,0 +1,41 @@
+use axum::{
+    extract::{Query, State},
+    response::{
+        sse::Event,
+        Sse,
+    },
+    Json,
+};
+
+use std::convert::Infallible;
+
+use tokio_stream::StreamExt;
+use monitor::probe::Probe;
+use futures_util::Stream;
+
+use crate::{
+    error::ApiError,
+    resources::filesystem::{self, Filesystems},
+    state::Hubs,
+};
+
+pub async fn filesystems_sse(
+    State(hubs): State<Hubs>,
+    Query(query): Query<filesystem::Query>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.filesystems.stream().map(move |x| {
+        Event::default().json_data(x.query(&query))
+    })
+    .filter_map(|x| x.map(Ok).ok());
+
+    Sse::new(s)
+}
+
+pub async fn filesystems(
+    State(hubs): State<Hubs>,
+    Query(query): Query<filesystem::Query>,
+) -> Result<Json<Filesystems>, ApiError> {
+    Ok(hubs.filesystems.probe().probe().await.map(|x| {
+        Json(x.query(&query))
+    })?)
+}
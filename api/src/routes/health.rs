@@ -0,0 +1,22 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+
+use crate::health::Checks;
+
+/// Every check, with a 503 if any of them
+/// is unhealthy so load balancers and
+/// uptime monitors can use it as is
+pub async fn health(State(checks): State<Checks>) -> impl IntoResponse {
+    let report = checks.report().await;
+
+    let status = match report.healthy {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    (status, Json(report))
+}
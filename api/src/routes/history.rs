@@ -0,0 +1,34 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::{
+    error::ApiError,
+    history::{self, Histories},
+};
+
+pub async fn history(
+    State(history): State<Histories>,
+    Path(resource): Path<String>,
+    Query(query): Query<history::Query>,
+) -> Result<Response, ApiError> {
+    Ok(match resource.as_str() {
+        "filesystems" => Json(history.filesystems.query(&query).await?).into_response(),
+        "network" => Json(history.network.query(&query).await?).into_response(),
+        "pressure" => Json(history.pressure.query(&query).await?).into_response(),
+        "sensors" => Json(history.sensors.query(&query).await?).into_response(),
+        "thermal" => Json(history.thermal.query(&query).await?).into_response(),
+        "disks" => Json(history.disks.query(&query).await?).into_response(),
+        "load" => Json(history.load.query(&query).await?).into_response(),
+        "mem" => Json(history.mem.query(&query).await?).into_response(),
+        "cpu" => Json(history.cpu.query(&query).await?).into_response(),
+        _ => (
+            StatusCode::NOT_FOUND,
+            format!("no history is kept for {}", resource),
+        )
+        .into_response(),
+    })
+}
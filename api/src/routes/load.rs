@@ -0,0 +1,22 @@
+use std::convert::Infallible;
+
+use axum::{extract::State, response::{sse::Event, Sse}, Json};
+use futures_util::Stream;
+use monitor::probe::Probe;
+use tokio_stream::StreamExt;
+
+use crate::{error::ApiError, resources::loadavg::LoadAvg, state::Hubs};
+
+pub async fn load_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.loadavg.stream().map(|x| {
+        Event::default().json_data(x)
+    })
+    .filter_map(|x| x.ok())
+    .map(Ok);
+
+    Sse::new(s)
+}
+
+pub async fn load(State(hubs): State<Hubs>) -> Result<Json<LoadAvg>, ApiError> {
+    Ok(hubs.loadavg.probe().probe().await.map(Json)?)
+}
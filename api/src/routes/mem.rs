@@ -1,4 +1,4 @@
-use axum::{response::{sse::Event, Sse}, Json};
+use axum::{extract::State, response::{sse::Event, Sse}, Json};
 
 use monitor::probe::Probe;
 use std::convert::Infallible;
@@ -8,11 +8,12 @@ use futures_util::Stream;
 
 use crate::{
     error::ApiError,
-    resources::memory::Memory
+    resources::memory::Memory,
+    state::Hubs,
 };
 
-pub async fn mem_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let x = Memory::stream().map(|x| {
+pub async fn mem_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let x = hubs.memory.stream().map(|x| {
         Event::default().json_data(x)
     })
     .filter_map(|x| x.ok())
@@ -21,6 +22,6 @@ pub async fn mem_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     Sse::new(x)
 }
 
-pub async fn mem() -> Result<Json<Memory>, ApiError> {
-    Ok(Memory::probe().await.map(Json)?)
+pub async fn mem(State(hubs): State<Hubs>) -> Result<Json<Memory>, ApiError> {
+    Ok(hubs.memory.probe().probe().await.map(Json)?)
 }
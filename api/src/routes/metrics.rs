@@ -0,0 +1,75 @@
+use axum::{
+    extract::State,
+    http::header,
+    response::IntoResponse,
+};
+
+use monitor::probe::Probe;
+
+use crate::{
+    metrics::{Exposition, Metrics},
+    state::Hubs,
+};
+
+/// Adds a probe's metrics, along with whether
+/// it succeeded so a failing probe is visible
+/// instead of its metrics just going missing
+fn add<T: Metrics, E>(out: &mut Exposition, probe: &str, res: Result<T, E>) {
+    out.gauge(
+        "probe_success",
+        "Whether the last probe of a resource succeeded.",
+        &[("probe", probe)],
+        res.is_ok() as u8 as f64,
+    );
+
+    if let Ok(x) = res {
+        out.add(&x);
+    }
+}
+
+pub async fn metrics(State(hubs): State<Hubs>) -> impl IntoResponse {
+    let (
+        filesystems,
+        pressure,
+        network,
+        sockets,
+        loadavg,
+        sensors,
+        memory,
+        uptime,
+        thermal,
+        disks,
+        usage,
+    ) = tokio::join!(
+        hubs.filesystems.probe().probe(),
+        hubs.pressure.probe().probe(),
+        hubs.network.probe().probe(),
+        hubs.sockets.probe().probe(),
+        hubs.loadavg.probe().probe(),
+        hubs.sensors.probe().probe(),
+        hubs.memory.probe().probe(),
+        hubs.uptime.probe().probe(),
+        hubs.cores.probe().probe(),
+        hubs.disks.probe().probe(),
+        hubs.usage.probe().probe(),
+    );
+
+    let mut out = Exposition::new();
+
+    add(&mut out, "cpu", usage);
+    add(&mut out, "thermal", thermal);
+    add(&mut out, "sensors", sensors);
+    add(&mut out, "memory", memory);
+    add(&mut out, "network", network);
+    add(&mut out, "uptime", uptime);
+    add(&mut out, "load", loadavg);
+    add(&mut out, "pressure", pressure);
+    add(&mut out, "disks", disks);
+    add(&mut out, "filesystems", filesystems);
+    add(&mut out, "sockets", sockets);
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        out.render(),
+    )
+}
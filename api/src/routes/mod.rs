@@ -1,13 +1,31 @@
 #![allow(unused_imports)]
 
 mod network;
+mod sensors;
 mod uptime;
+mod load;
+mod pressure;
+mod process;
+mod disk;
+mod filesystem;
+mod health;
+mod history;
+mod metrics;
 mod root;
 mod mem;
 mod cpu;
 
 pub use network::*;
+pub use sensors::*;
 pub use uptime::*;
+pub use load::*;
+pub use pressure::*;
+pub use process::*;
+pub use disk::*;
+pub use filesystem::*;
+pub use health::*;
+pub use history::*;
+pub use metrics::*;
 pub use network::*;
 pub use uptime::*;
 pub use mem::*;
@@ -15,3 +33,17 @@ pub use cpu::*;
 pub use root::*;
 pub use mem::*;
 pub use cpu::*;
+
+#[derive(
+    serde::Deserialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// Whether to serve the raw counters
+/// or the rates between samples
+pub struct RateQuery {
+    #[serde(default)]
+    pub rate: bool,
+}
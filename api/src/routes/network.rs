@@ -1,14 +1,52 @@
 use std::convert::Infallible;
 
-use axum::{response::{sse::Event, Sse}, Json};
-use futures_util::Stream;
+use axum::{
+    extract::{Query, State},
+    response::{sse::Event, IntoResponse, Response, Sse},
+    Json,
+};
+use futures_util::{future::Either, Stream};
 use monitor::probe::Probe;
+use serde::Deserialize;
 use tokio_stream::StreamExt;
 
-use crate::{error::ApiError, resources::network::Network};
+use crate::{
+    error::ApiError,
+    history,
+    resources::{network, socket::{self, Sockets}},
+    state::Hubs,
+};
 
-pub async fn network_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let s = Network::stream().map(|x| {
+#[derive(
+    Deserialize,
+    Default,
+    Debug,
+    Clone,
+)]
+
+/// The rate mode of /network,
+/// and its interface filters
+pub struct NetworkQuery {
+    #[serde(default)]
+    pub rate: bool,
+    #[serde(flatten)]
+    pub filter: network::Query,
+}
+
+pub async fn network_events_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut prev = None::<network::Network>;
+
+    let s = hubs.network.stream().map(move |x| {
+        let events = prev.as_ref()
+            .map(|prev| x.events_since(prev, history::now()))
+            .unwrap_or_default();
+
+        prev = Some(x);
+
+        futures_util::stream::iter(events)
+    });
+
+    let s = futures_util::StreamExt::flatten(s).map(|x| {
         Event::default().json_data(x)
     })
     .filter_map(|x| x.ok())
@@ -17,6 +55,56 @@ pub async fn network_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>>
     Sse::new(s)
 }
 
-pub async fn network() -> Result<Json<Network>, ApiError> {
-    Ok(Network::probe().await.map(Json)?)
+pub async fn network_sockets_sse(
+    State(hubs): State<Hubs>,
+    Query(query): Query<socket::Query>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.sockets.stream().map(move |x| {
+        Event::default().json_data(x.query(&query))
+    })
+    .filter_map(|x| x.ok())
+    .map(Ok);
+
+    Sse::new(s)
+}
+
+pub async fn network_sse(
+    State(hubs): State<Hubs>,
+    Query(query): Query<NetworkQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let filter = query.filter;
+
+    let s = match query.rate {
+        true => Either::Left(hubs.network_rates.stream().map(move |x| {
+            Event::default().json_data(x.query(&filter))
+        })),
+        false => Either::Right(hubs.network.stream().map(move |x| {
+            Event::default().json_data(x.query(&filter))
+        })),
+    }
+    .filter_map(|x| x.ok())
+    .map(Ok);
+
+    Sse::new(s)
+}
+
+pub async fn network(
+    State(hubs): State<Hubs>,
+    Query(query): Query<NetworkQuery>,
+) -> Result<Response, ApiError> {
+    let filter = &query.filter;
+
+    Ok(match query.rate {
+        true => Json(hubs.network_rates.probe().measure().await?.query(filter)).into_response(),
+        false => Json(hubs.network.probe().probe().await?.query(filter)).into_response(),
+    })
+}
+
+pub async fn network_sockets(
+    State(hubs): State<Hubs>,
+    Query(query): Query<socket::Query>,
+) -> Result<Json<Sockets>, ApiError> {
+    Ok(hubs.sockets.probe().probe().await.map(|x| {
+        Json(x.query(&query))
+    })?)
 }
@@ -0,0 +1,22 @@
+use std::convert::Infallible;
+
+use axum::{extract::State, response::{sse::Event, Sse}, Json};
+use futures_util::Stream;
+use monitor::probe::Probe;
+use tokio_stream::StreamExt;
+
+use crate::{error::ApiError, resources::pressure::Pressure, state::Hubs};
+
+pub async fn pressure_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.pressure.stream().map(|x| {
+        Event::default().json_data(x)
+    })
+    .filter_map(|x| x.ok())
+    .map(Ok);
+
+    Sse::new(s)
+}
+
+pub async fn pressure(State(hubs): State<Hubs>) -> Result<Json<Pressure>, ApiError> {
+    Ok(hubs.pressure.probe().probe().await.map(Json)?)
+}
@@ -0,0 +1,63 @@
+use axum::{
+    extract::{Query, State},
+    response::{
+        sse::Event,
+        Sse,
+    },
+    Json,
+};
+
+use std::convert::Infallible;
+
+use tokio_stream::StreamExt;
+use monitor::probe::Probe;
+use futures_util::Stream;
+
+use crate::{
+    error::ApiError,
+    resources::process::{self, Processes, Top, TopQuery, Tree},
+    state::Hubs,
+};
+
+pub async fn processes_top_sse(
+    State(hubs): State<Hubs>,
+    Query(query): Query<TopQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.top.stream().map(move |x| {
+        Event::default().json_data(x.query(&query))
+    })
+    .filter_map(|x| x.map(Ok).ok());
+
+    Sse::new(s)
+}
+
+pub async fn processes_tree_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.top.stream().map(|x| {
+        Event::default().json_data(Tree::from(x))
+    })
+    .filter_map(|x| x.map(Ok).ok());
+
+    Sse::new(s)
+}
+
+pub async fn processes(
+    State(hubs): State<Hubs>,
+    Query(query): Query<process::Query>,
+) -> Result<Json<Processes>, ApiError> {
+    Ok(hubs.processes.probe().probe().await.map(|x| {
+        Json(x.query(&query))
+    })?)
+}
+
+pub async fn processes_top(
+    State(hubs): State<Hubs>,
+    Query(query): Query<TopQuery>,
+) -> Result<Json<Top>, ApiError> {
+    Ok(hubs.top.probe().measure().await.map(|x| {
+        Json(x.query(&query))
+    })?)
+}
+
+pub async fn processes_tree(State(hubs): State<Hubs>) -> Result<Json<Tree>, ApiError> {
+    Ok(hubs.top.probe().measure().await.map(|x| Json(Tree::from(x)))?)
+}
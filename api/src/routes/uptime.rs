@@ -1,14 +1,14 @@
 use std::convert::Infallible;
 
-use axum::{response::{sse::Event, Sse}, Json};
+use axum::{extract::State, response::{sse::Event, Sse}, Json};
 use futures_util::Stream;
 use monitor::probe::Probe;
 use tokio_stream::StreamExt;
 
-use crate::{error::ApiError, resources::uptime::Uptime};
+use crate::{error::ApiError, resources::uptime::Uptime, state::Hubs};
 
-pub async fn uptime_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
-    let s = Uptime::stream().map(|x| {
+pub async fn uptime_sse(State(hubs): State<Hubs>) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let s = hubs.uptime.stream().map(|x| {
         Event::default().json_data(x)
     })
     .filter_map(|x| x.ok())
@@ -17,6 +17,6 @@ pub async fn uptime_sse() -> Sse<impl Stream<Item = Result<Event, Infallible>>>
     Sse::new(s)
 }
 
-pub async fn uptime() -> Result<Json<Uptime>, ApiError> {
-    Ok(Uptime::probe().await.map(Json)?)
+pub async fn uptime(State(hubs): State<Hubs>) -> Result<Json<Uptime>, ApiError> {
+    Ok(hubs.uptime.probe().probe().await.map(Json)?)
 }
@@ -0,0 +1,112 @@
+use axum::extract::FromRef;
+use monitor::hub::Hub;
+
+use crate::{
+    health::Checks,
+    history::Histories,
+    resources::{
+        cpu::{
+            CoresPercentProbe, CoresProbe, CoresUsageProbe, CpuProbe, InfoProbe, UsagePercentProbe,
+            UsageProbe,
+        },
+        disk::{DiskRatesProbe, DisksProbe},
+        filesystem::FilesystemsProbe,
+        hwmon::SensorsProbe,
+        loadavg::LoadAvgProbe,
+        network::{NetworkProbe, NetworkRatesProbe},
+        memory::MemoryProbe,
+        pressure::PressureProbe,
+        process::{ProcessesProbe, TopProbe},
+        socket::SocketsProbe,
+        uptime::UptimeProbe,
+        Source,
+    },
+};
+
+#[derive(
+    Default,
+    Clone,
+)]
+
+/// One sampling [`Hub`] per resource, shared
+/// by every realtime (sse) subscriber so each
+/// resource is only ever probed by one loop
+pub struct Hubs {
+    pub cores_percent: Hub<CoresPercentProbe>,
+    pub cores_usage: Hub<CoresUsageProbe>,
+    pub usage_percent: Hub<UsagePercentProbe>,
+    pub network_rates: Hub<NetworkRatesProbe>,
+    pub network: Hub<NetworkProbe>,
+    pub sensors: Hub<SensorsProbe>,
+    pub disk_rates: Hub<DiskRatesProbe>,
+    pub memory: Hub<MemoryProbe>,
+    pub disks: Hub<DisksProbe>,
+    pub filesystems: Hub<FilesystemsProbe>,
+    pub pressure: Hub<PressureProbe>,
+    pub processes: Hub<ProcessesProbe>,
+    pub top: Hub<TopProbe>,
+    pub sockets: Hub<SocketsProbe>,
+    pub uptime: Hub<UptimeProbe>,
+    pub loadavg: Hub<LoadAvgProbe>,
+    pub cores: Hub<CoresProbe>,
+    pub usage: Hub<UsageProbe>,
+    pub info: Hub<InfoProbe>,
+    pub cpu: Hub<CpuProbe>,
+}
+
+impl From<&Source> for Hubs {
+    fn from(source: &Source) -> Self {
+        Self {
+            cores_percent: Hub::new(source.into()),
+            cores_usage: Hub::new(source.into()),
+            usage_percent: Hub::new(source.into()),
+            network_rates: Hub::new(source.into()),
+            network: Hub::new(source.into()),
+            sensors: Hub::new(source.into()),
+            disk_rates: Hub::new(source.into()),
+            memory: Hub::new(source.into()),
+            disks: Hub::new(source.into()),
+            filesystems: Hub::new(source.into()),
+            pressure: Hub::new(source.into()),
+            processes: Hub::new(source.into()),
+            top: Hub::new(source.into()),
+            sockets: Hub::new(source.into()),
+            uptime: Hub::new(source.into()),
+            loadavg: Hub::new(source.into()),
+            cores: Hub::new(source.into()),
+            usage: Hub::new(source.into()),
+            info: Hub::new(source.into()),
+            cpu: Hub::new(source.into()),
+        }
+    }
+}
+
+#[derive(
+    Clone,
+)]
+
+/// Everything the routes share, handlers
+/// extract just the part they need
+pub struct AppState {
+    pub history: Histories,
+    pub health: Checks,
+    pub hubs: Hubs,
+}
+
+impl FromRef<AppState> for Hubs {
+    fn from_ref(state: &AppState) -> Self {
+        state.hubs.clone()
+    }
+}
+
+impl FromRef<AppState> for Histories {
+    fn from_ref(state: &AppState) -> Self {
+        state.history.clone()
+    }
+}
+
+impl FromRef<AppState> for Checks {
+    fn from_ref(state: &AppState) -> Self {
+        state.health.clone()
+    }
+}
@@ -0,0 +1,549 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Deserialize,
+    Serialize,
+};
+
+use serde_json::{Number, Value};
+
+use thiserror::Error;
+
+use crate::history::Sample;
+
+// How many segments a tier's retention is
+// split over, old data is dropped a whole
+// segment at a time
+const SEGMENTS: u32 = 8;
+
+#[derive(
+    PartialEq, Eq,
+    Deserialize,
+    Serialize,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// A resolution samples are downsampled to,
+/// and how long they are kept at it
+pub struct Tier {
+    pub resolution: Duration,
+    pub retention: Duration,
+}
+
+impl Tier {
+    pub fn new(resolution: Duration, retention: Duration) -> Self {
+        Self {
+            resolution,
+            retention,
+        }
+    }
+
+    fn resolution_ms(&self) -> u64 {
+        (self.resolution.as_millis() as u64).max(1)
+    }
+
+    /// The milliseconds each segment file covers
+    fn span(&self) -> u64 {
+        ((self.retention / SEGMENTS).as_millis() as u64)
+            .max(self.resolution_ms())
+    }
+}
+
+#[derive(
+    Error,
+    Debug,
+)]
+
+pub enum Error {
+    #[error("io error occurred in the history store: {0}")]
+    Io(#[from] io::Error),
+    #[error("failed to serialize a sample: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// The fields that tell apart the elements of an
+/// array, e.g. a disk's name or a filesystem's
+/// mountpoint, which come and go between samples
+const IDENTITY: &[&str] = &[
+    "mountpoint",
+    "package",
+    "label",
+    "index",
+    "count",
+    "name",
+    "path",
+    "kind",
+    "id",
+];
+
+/// The identity of an array element, None if
+/// it has none and is matched by position
+fn identity(field: impl Fn(&str) -> Option<Value>) -> Option<Vec<Value>> {
+    let identity = IDENTITY.iter()
+        .map(|k| field(k).unwrap_or(Value::Null))
+        .collect::<Vec<_>>();
+
+    identity.iter().any(|x| !x.is_null())
+        .then_some(identity)
+}
+
+/// The running mean and max of the samples in
+/// a window, field by field. Numbers are averaged
+/// and maxed, anything else (names, states) is
+/// kept from the latest sample. Objects are
+/// matched up by key, and array elements by
+/// their [`IDENTITY`] fields, or by position
+/// without any, so an element only counts the
+/// samples it was in.
+enum Aggregate {
+    Number {
+        sum: f64,
+        count: u64,
+        max: Number,
+    },
+    Array(Vec<Aggregate>),
+    Object(BTreeMap<String, Aggregate>),
+    Other(Value),
+}
+
+impl Aggregate {
+    fn new(value: &Value) -> Self {
+        match value {
+            Value::Number(x) => Self::Number {
+                sum: x.as_f64().unwrap_or_default(),
+                max: x.clone(),
+                count: 1,
+            },
+            Value::Array(x) => Self::Array(x.iter().map(Self::new).collect()),
+            Value::Object(x) => Self::Object(
+                x.iter()
+                    .map(|(k, v)| (k.clone(), Self::new(v)))
+                    .collect()
+            ),
+            x => Self::Other(x.clone()),
+        }
+    }
+
+    fn add(&mut self, value: &Value) {
+        match (self, value) {
+            (Self::Number { sum, count, max }, Value::Number(x)) => {
+                let x_f64 = x.as_f64().unwrap_or_default();
+
+                if x_f64 > max.as_f64().unwrap_or_default() {
+                    *max = x.clone();
+                }
+
+                *sum += x_f64;
+                *count += 1;
+            }
+            (Self::Array(acc), Value::Array(x)) => {
+                for (i, x) in x.iter().enumerate() {
+                    let found = match Self::identity_of(x) {
+                        Some(id) => acc.iter().position(|x| x.identity().as_ref() == Some(&id)),
+                        None => (i < acc.len()).then_some(i),
+                    };
+
+                    match found {
+                        Some(i) => acc[i].add(x),
+                        None => acc.push(Self::new(x)),
+                    }
+                }
+            }
+            (Self::Object(acc), Value::Object(x)) => {
+                for (k, x) in x {
+                    match acc.get_mut(k) {
+                        Some(acc) => acc.add(x),
+                        None => {
+                            acc.insert(k.clone(), Self::new(x));
+                        }
+                    }
+                }
+            }
+            // a missing value doesn't
+            // undo the ones seen so far
+            (_, Value::Null) => (),
+            (acc, x) => *acc = Self::new(x),
+        }
+    }
+
+    fn identity_of(value: &Value) -> Option<Vec<Value>> {
+        let x = value.as_object()?;
+
+        identity(|k| x.get(k).cloned())
+    }
+
+    fn identity(&self) -> Option<Vec<Value>> {
+        match self {
+            // identical in every sample it
+            // counts, so the max is the value
+            Self::Object(x) => identity(|k| x.get(k).map(Self::max)),
+            _ => None,
+        }
+    }
+
+    fn mean(&self) -> Value {
+        match self {
+            Self::Number { sum, count, max } => {
+                let mean = sum / *count as f64;
+
+                // integers stay integers, so they
+                // still deserialize as such
+                if max.is_u64() {
+                    Value::from(mean.round() as u64)
+                } else if max.is_i64() {
+                    Value::from(mean.round() as i64)
+                } else {
+                    Value::from(mean)
+                }
+            }
+            Self::Array(x) => Value::Array(x.iter().map(Self::mean).collect()),
+            Self::Object(x) => Value::Object(
+                x.iter()
+                    .map(|(k, v)| (k.clone(), v.mean()))
+                    .collect()
+            ),
+            Self::Other(x) => x.clone(),
+        }
+    }
+
+    fn max(&self) -> Value {
+        match self {
+            Self::Number { max, .. } => Value::Number(max.clone()),
+            Self::Array(x) => Value::Array(x.iter().map(Self::max).collect()),
+            Self::Object(x) => Value::Object(
+                x.iter()
+                    .map(|(k, v)| (k.clone(), v.max()))
+                    .collect()
+            ),
+            Self::Other(x) => x.clone(),
+        }
+    }
+
+    /// The sample of the window starting at timestamp
+    fn sample(&self, timestamp: u64) -> Sample<Value> {
+        Sample {
+            max: Some(self.max()),
+            value: self.mean(),
+            timestamp,
+        }
+    }
+}
+
+/// The append only segment files of a
+/// single [`Tier`], each named after the
+/// millisecond its span starts at
+///
+/// The window being aggregated is only
+/// written once the next one starts, or
+/// once the segments are dropped
+struct Segments {
+    pending: Option<(u64, Aggregate)>,
+    file: Option<(u64, File)>,
+    window: Option<u64>,
+    dir: PathBuf,
+    tier: Tier,
+}
+
+impl Segments {
+    fn open(dir: PathBuf, tier: Tier) -> Result<Self, Error> {
+        fs::create_dir_all(&dir)?;
+
+        let mut segments = Self {
+            pending: None,
+            file: None,
+            window: None,
+            dir,
+            tier,
+        };
+
+        // only the newest segment was being
+        // appended to, so only it can have
+        // been cut off mid sample
+        if let Some((_, path)) = segments.list()?.last() {
+            segments.window = recover(path)?
+                .map(|x| x / tier.resolution_ms());
+        }
+
+        Ok(segments)
+    }
+
+    /// Every segment, oldest first
+    fn list(&self) -> io::Result<Vec<(u64, PathBuf)>> {
+        let mut segments = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+
+            let start = path.extension()
+                .filter(|x| *x == "jsonl")
+                .and(path.file_stem())
+                .and_then(|x| x.to_str())
+                .and_then(|x| x.parse::<u64>().ok());
+
+            if let Some(start) = start {
+                segments.push((start, path));
+            }
+        }
+
+        segments.sort();
+
+        Ok(segments)
+    }
+
+    /// Adds a value to its resolution window,
+    /// writing out the previous window once
+    /// it's done. Values for windows already
+    /// written are dropped.
+    fn append(&mut self, timestamp: u64, value: &Value) -> Result<(), Error> {
+        let window = timestamp / self.tier.resolution_ms();
+
+        if self.window.is_some_and(|x| x >= window) {
+            return Ok(());
+        }
+
+        match &mut self.pending {
+            Some((x, acc)) if *x == window => {
+                acc.add(value);
+
+                return Ok(());
+            }
+            Some((x, _)) if *x > window => return Ok(()),
+            _ => (),
+        }
+
+        match self.pending.replace((window, Aggregate::new(value))) {
+            Some((window, acc)) => self.write(window, &acc),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes out the window being aggregated
+    fn flush(&mut self) -> Result<(), Error> {
+        match self.pending.take() {
+            Some((window, acc)) => self.write(window, &acc),
+            None => Ok(()),
+        }
+    }
+
+    fn write(&mut self, window: u64, acc: &Aggregate) -> Result<(), Error> {
+        let timestamp = window * self.tier.resolution_ms();
+
+        let mut line = serde_json::to_string(&acc.sample(timestamp))?;
+
+        line.push('\n');
+
+        let span = self.tier.span();
+        let start = timestamp / span * span;
+
+        let file = match &mut self.file {
+            Some((x, file)) if *x == start => file,
+            _ => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(self.dir.join(format!("{}.jsonl", start)))?;
+
+                self.prune(timestamp)?;
+
+                &mut self.file.insert((start, file)).1
+            }
+        };
+
+        // one write, so a crash can only
+        // ever cut off the last line
+        file.write_all(line.as_bytes())?;
+
+        self.window = Some(window);
+
+        Ok(())
+    }
+
+    /// Removes the segments that only
+    /// hold samples past the retention
+    fn prune(&self, timestamp: u64) -> io::Result<()> {
+        let oldest = timestamp
+            .saturating_sub(self.tier.retention.as_millis() as u64);
+
+        for (start, path) in self.list()? {
+            if start + self.tier.span() <= oldest {
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The samples between since and until, including
+    /// the window still being aggregated
+    fn read<T: DeserializeOwned>(&self, since: u64, until: u64) -> Result<Vec<Sample<T>>, Error> {
+        let span = self.tier.span();
+
+        let mut samples = Vec::new();
+
+        for (start, path) in self.list()? {
+            if start > until || start + span <= since {
+                continue;
+            }
+
+            let segment = match fs::read_to_string(&path) {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                x => x?,
+            };
+
+            samples.extend(
+                segment.lines()
+                    .filter_map(|x| serde_json::from_str::<Sample<T>>(x).ok())
+                    .filter(|x| x.timestamp >= since && x.timestamp <= until)
+            );
+        }
+
+        if let Some((window, acc)) = &self.pending {
+            let timestamp = window * self.tier.resolution_ms();
+
+            if timestamp >= since && timestamp <= until {
+                let sample = serde_json::to_value(acc.sample(timestamp))?;
+
+                samples.extend(serde_json::from_value::<Sample<T>>(sample).ok());
+            }
+        }
+
+        Ok(samples)
+    }
+}
+
+impl Drop for Segments {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            tracing::warn!("failed to persist the last window of samples: {}", e);
+        }
+    }
+}
+
+/// Cuts off whatever follows the last whole
+/// sample of a segment, e.g. a half written
+/// line after a crash, so new samples don't
+/// get glued onto it. Returns the timestamp
+/// of that last sample.
+fn recover(path: &Path) -> io::Result<Option<u64>> {
+    let segment = fs::read(path)?;
+
+    let mut valid = 0;
+    let mut last = None;
+    let mut offset = 0;
+
+    for line in segment.split_inclusive(|x| *x == b'\n') {
+        offset += line.len();
+
+        if !line.ends_with(b"\n") {
+            break;
+        }
+
+        if let Ok(x) = serde_json::from_slice::<Sample<IgnoredAny>>(line) {
+            last = Some(x.timestamp);
+            valid = offset;
+        }
+    }
+
+    if valid < segment.len() {
+        OpenOptions::new()
+            .write(true)
+            .open(path)?
+            .set_len(valid as u64)?;
+    }
+
+    Ok(last)
+}
+
+/// Samples persisted on disk, downsampled into
+/// every [`Tier`] as the mean of each of its
+/// resolution windows, along with the max
+///
+/// Writes are plain appends, so a history
+/// survives restarts, and one cut short by
+/// an unclean shutdown is repaired on open.
+/// The window each tier is aggregating is
+/// only written once it's done, or on
+/// [`Store::flush`], so an unclean shutdown
+/// loses those.
+pub struct Store<T> {
+    tiers: Vec<Mutex<Segments>>,
+    _output: PhantomData<fn() -> T>,
+}
+
+impl<T> Store<T> {
+    /// Opens the store in dir, one sub
+    /// directory per tier resolution
+    pub fn open(dir: impl AsRef<Path>, tiers: &[Tier]) -> Result<Self, Error> {
+        let tiers = tiers.iter()
+            .map(|x| {
+                let dir = dir.as_ref()
+                    .join(format!("{}s", x.resolution.as_secs_f64()));
+
+                Segments::open(dir, *x).map(Mutex::new)
+            })
+            .try_collect()?;
+
+        Ok(Self {
+            tiers,
+            _output: PhantomData,
+        })
+    }
+
+    pub fn tiers(&self) -> Vec<Tier> {
+        self.tiers.iter()
+            .map(|x| x.lock().unwrap_or_else(|x| x.into_inner()).tier)
+            .collect()
+    }
+
+    /// Writes out the window every tier is
+    /// aggregating. Later samples in those
+    /// windows are dropped, so this is
+    /// meant for shutting down.
+    pub fn flush(&self) -> Result<(), Error> {
+        for tier in &self.tiers {
+            tier.lock()
+                .unwrap_or_else(|x| x.into_inner())
+                .flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Serialize> Store<T> {
+    pub fn append(&self, sample: &Sample<T>) -> Result<(), Error> {
+        let value = serde_json::to_value(&sample.value)?;
+
+        for tier in &self.tiers {
+            tier.lock()
+                .unwrap_or_else(|x| x.into_inner())
+                .append(sample.timestamp, &value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: DeserializeOwned> Store<T> {
+    /// The samples of the tier at index
+    /// between since and until, inclusive
+    pub fn read(&self, tier: usize, since: u64, until: u64) -> Result<Vec<Sample<T>>, Error> {
+        match self.tiers.get(tier) {
+            Some(x) => x.lock()
+                .unwrap_or_else(|x| x.into_inner())
+                .read(since, until),
+            None => Ok(Vec::new()),
+        }
+    }
+}
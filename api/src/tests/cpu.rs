@@ -1,8 +1,12 @@
-use std::fs::remove_dir_all;
+use crate::resources::cpu::{
+    Availability, Core, CoreError, CpuError, CpuProbe, Section, CorePercent, CoreUsage, CoresPercentProbe, CoresProbe, CoresUsageProbe, Info,
+    InfoError, InfoProbe, Package, Sensor, Thermal, Usage, UsageError, UsagePercent,
+    UsagePercentProbe, UsageProbe,
+};
 
-use crate::resources::cpu::{Core, CoreError, Cores, Info, InfoError, Usage, UsageError};
 use monitor::probe::Probe;
-use tokio::{fs::File, io::AsyncWriteExt};
+
+use super::TempDir;
 
 const USAGE_STR: &str = "cpu  1 2 3 4 5 6 7 8 9 10";
 
@@ -20,17 +24,14 @@ const USAGE: Usage = Usage {
 };
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_usage_invalid_cpu_line() -> crate::Any {
     let data = format!("{} 0 0 0 0 0", USAGE_STR);
 
-    super::point_env_file(
-        "STAT",
-        "/tmp/stat",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Usage::probe().await
+    let stat = tmp.file("stat", &data).await?;
+
+    let err = UsageProbe::new(stat).probe().await
         .unwrap_err();
 
     assert!(matches!(err, UsageError::InvalidCpuLine));
@@ -39,17 +40,14 @@ pub async fn test_probe_cpu_usage_invalid_cpu_line() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_usage_parse_int() -> crate::Any {
     let data = USAGE_STR.replace("10", "abc");
 
-    super::point_env_file(
-        "STAT",
-        "/tmp/stat",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Usage::probe().await
+    let stat = tmp.file("stat", &data).await?;
+
+    let err = UsageProbe::new(stat).probe().await
         .unwrap_err();
 
     assert!(matches!(err, UsageError::ParseInt(_)));
@@ -58,17 +56,14 @@ pub async fn test_probe_cpu_usage_parse_int() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_usage_missing() -> crate::Any {
     let data = USAGE_STR.replace("cpu", "");
 
-    super::point_env_file(
-        "STAT",
-        "/tmp/stat",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
+
+    let stat = tmp.file("stat", &data).await?;
 
-    let err = Usage::probe().await
+    let err = UsageProbe::new(stat).probe().await
         .unwrap_err();
 
     assert!(matches!(err, UsageError::CpuMissing));
@@ -77,17 +72,14 @@ pub async fn test_probe_cpu_usage_missing() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_usage_success() -> crate::Any {
     let data = USAGE_STR;
 
-    super::point_env_file(
-        "STAT",
-        "/tmp/stat",
-        data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
+
+    let stat = tmp.file("stat", data).await?;
 
-    let usage = Usage::probe().await?;
+    let usage = UsageProbe::new(stat).probe().await?;
 
     assert_eq!(usage, USAGE);
 
@@ -95,11 +87,120 @@ pub async fn test_probe_cpu_usage_success() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_usage_io() -> crate::Any {
-    std::env::set_var("STAT", "/tmp/not/a/file");
+    let err = UsageProbe::new("/tmp/not/a/file").probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, UsageError::Io(_)));
+
+    Ok(())
+}
 
-    let err = Usage::probe().await
+// 100 jiffies after USAGE_STR, guest
+// time is already part of user / nice
+const USAGE_NEXT_STR: &str = "cpu  21 2 23 44 15 6 7 18 19 10";
+
+fn usage_percent() -> UsagePercent {
+    UsagePercent {
+        busy: 50.0,
+        user: 20.0,
+        nice: 0.0,
+        system: 20.0,
+        idle: 40.0,
+        iowait: 10.0,
+        irq: 0.0,
+        softirq: 0.0,
+        steal: 10.0,
+    }
+}
+
+#[test]
+pub fn test_cpu_usage_percent_since() {
+    let next = Usage {
+        user: 21,
+        system: 23,
+        idle: 44,
+        iowait: 15,
+        steal: 18,
+        guest: 19,
+        ..USAGE
+    };
+
+    assert_eq!(next.percent_since(&USAGE), Some(usage_percent()));
+    assert_eq!(USAGE.percent_since(&USAGE), None);
+}
+
+#[test]
+pub fn test_cpu_usage_percent_since_reset() {
+    let reset = Usage {
+        idle: 0,
+        ..USAGE
+    };
+
+    assert_eq!(reset.percent_since(&USAGE), None);
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_usage_percent_success() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let stat = tmp.file("stat", USAGE_STR).await?;
+
+    let probe = UsagePercentProbe::new(
+        UsageProbe::new(&stat)
+    );
+
+    // no time passed between the first
+    // two samples, so there's no percent
+    let err = probe.probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, UsageError::CounterReset(_)));
+
+    tmp.file("stat", USAGE_NEXT_STR).await?;
+
+    assert_eq!(probe.probe().await?, usage_percent());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_usage_percent_reset() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let stat = tmp.file("stat", USAGE_NEXT_STR).await?;
+
+    let probe = UsagePercentProbe::new(
+        UsageProbe::new(&stat)
+    );
+
+    // only sets the baseline
+    let _ = probe.probe().await;
+
+    tmp.file("stat", USAGE_STR).await?;
+
+    // starts over from the reset counters
+    // instead of reporting a negative delta,
+    // but no time passed since the reset
+    let err = probe.probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, UsageError::CounterReset(_)));
+
+    tmp.file("stat", USAGE_NEXT_STR).await?;
+
+    assert_eq!(probe.probe().await?, usage_percent());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_usage_percent_io() -> crate::Any {
+    let probe = UsagePercentProbe::new(
+        UsageProbe::new("/tmp/not/a/file")
+    );
+
+    let err = probe.probe().await
         .unwrap_err();
 
     assert!(matches!(err, UsageError::Io(_)));
@@ -107,85 +208,141 @@ pub async fn test_probe_cpu_usage_io() -> crate::Any {
     Ok(())
 }
 
-struct SetDown;
+fn cores_stat(cores: &[&str]) -> String {
+    let cores = cores.iter().enumerate()
+        .map(|(i, x)| x.replace("cpu ", &format!("cpu{i}")))
+        .collect::<Vec<String>>()
+        .join("\n");
 
-impl Drop for SetDown {
-    fn drop(&mut self) {
-        remove_dir_all("/tmp/coretemp").unwrap();
-    }
+    format!("{USAGE_STR}\n{cores}\nintr 1 2 3\nctxt 4\n")
 }
 
-async fn setup(
-    crit_alarm: impl AsRef<str>,
-    label: impl AsRef<str>,
-) -> anyhow::Result<SetDown> {
-    super::point_env_dir(
-        "CORETEMP",
-        "/tmp/coretemp",
-        []
-    ).await?;
+#[tokio::test]
+pub async fn test_probe_cpu_usage_cores_success() -> crate::Any {
+    let tmp = TempDir::new().await?;
 
-    async fn write_to(
-        data: impl AsRef<str>,
-        path: impl AsRef<str>
-    ) -> crate::Any {
-        let mut file = File::create(path.as_ref())
-            .await?;
+    let stat = tmp.file("stat", cores_stat(&[USAGE_STR, USAGE_STR])).await?;
 
-        file.write_all(
-            data.as_ref().as_bytes()
-        ).await?;
+    let cores = CoresUsageProbe::new(stat).probe().await?;
 
-        Ok(())
-    }
+    assert_eq!(cores, vec![
+        CoreUsage {
+            usage: USAGE,
+            cpu: 0,
+        },
+        CoreUsage {
+            usage: USAGE,
+            cpu: 1,
+        },
+    ]);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_usage_cores_invalid_cpu_line() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let data = cores_stat(&[USAGE_STR, "cpu  1 2 3"]);
+
+    let stat = tmp.file("stat", data).await?;
+
+    let err = CoresUsageProbe::new(stat).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, UsageError::InvalidCpuLine));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_usage_cores_percent_success() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let stat = tmp.file("stat", cores_stat(&[USAGE_STR, USAGE_STR])).await?;
+
+    let probe = CoresPercentProbe::new(
+        CoresUsageProbe::new(&stat)
+    );
 
-    let coretemp = "/tmp/coretemp";
+    probe.probe().await?;
 
-    write_to(
-        crit_alarm.as_ref(),
-        format!("{}/temp1_crit_alarm", coretemp),
+    // cpu1 resets, so it is left
+    // out of this round
+    tmp.file("stat", cores_stat(&[USAGE_NEXT_STR, "cpu  0 0 0 0 0 0 0 0 0 0"])).await?;
+
+    assert_eq!(probe.probe().await?, vec![
+        CorePercent {
+            percent: usage_percent(),
+            cpu: 0,
+        },
+    ]);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_usage_cores_io() -> crate::Any {
+    let err = CoresUsageProbe::new("/tmp/not/a/file").probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, UsageError::Io(_)));
+
+    Ok(())
+}
+
+async fn setup(
+    crit_alarm: impl AsRef<str>,
+    label: impl AsRef<str>,
+) -> anyhow::Result<TempDir> {
+    let coretemp = TempDir::new().await?;
+
+    coretemp.file(
+        "temp1_crit_alarm",
+        crit_alarm,
     ).await?;
 
-    write_to(
+    coretemp.file(
+        "temp1_input",
         "47000",
-        format!("{}/temp1_input", coretemp),
     ).await?;
 
-    write_to(
+    coretemp.file(
+        "temp1_crit",
         "100000",
-        format!("{}/temp1_crit", coretemp),
     ).await?;
 
-    write_to(
-        label.as_ref(),
-        format!("{}/temp1_label", coretemp),
+    coretemp.file(
+        "temp1_label",
+        label,
     ).await?;
 
-    write_to(
+    coretemp.file(
+        "temp1_max",
         "100000",
-        format!("{}/temp1_max", coretemp),
     ).await?;
 
-    Ok(SetDown)
+    Ok(coretemp)
 }
 
 fn cores() -> Vec<Core> {
     vec![
         Core {
+            crit_alarm: false,
             max_temp: 100000,
             crit: 100000,
             temp: 47000,
+            package: None,
             count: 0,
         }
     ]
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_cores_invalid_label() -> crate::Any {
-    let _setdown = setup("0", "Core").await?;
+    let coretemp = setup("0", "Core").await?;
 
-    let err = Cores::probe().await
+    let err = CoresProbe::new(coretemp.path()).probe().await
         .unwrap_err();
 
     assert!(matches!(err, CoreError::InvalidLabel(_)));
@@ -194,11 +351,10 @@ pub async fn test_probe_cpu_cores_invalid_label() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_cores_parse_int() -> crate::Any {
-    let _setdown = setup("0", "Core a").await?;
+    let coretemp = setup("0", "Core a").await?;
 
-    let err = Cores::probe().await
+    let err = CoresProbe::new(coretemp.path()).probe().await
         .unwrap_err();
 
     assert!(matches!(err, CoreError::ParseInt(_)));
@@ -207,23 +363,81 @@ pub async fn test_probe_cpu_cores_parse_int() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_cores_success() -> crate::Any {
-    let _setdown = setup("0", "Core 0").await?;
+    let coretemp = setup("0", "Core 0").await?;
 
-    let res = Cores::probe().await?;
+    let res = CoresProbe::new(coretemp.path()).probe().await?;
 
-    assert_eq!(cores(), res);
+    assert_eq!(cores(), res.cores);
 
     Ok(())
 }
 
 #[tokio::test]
-#[serial_test::serial]
-pub async fn test_probe_cpu_cores_io() -> crate::Any {
-    std::env::set_var("CORETEMP", "/tmp/not/a/file");
+pub async fn test_probe_cpu_cores_crit_alarm() -> crate::Any {
+    let coretemp = setup("1", "Core 0").await?;
+
+    let res = CoresProbe::new(coretemp.path()).probe().await?;
+
+    assert!(res.cores[0].crit_alarm);
+    assert!(res.crit_alarm());
 
-    let err = Cores::probe().await
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_cores_packages() -> crate::Any {
+    let coretemp = setup("0", "Package id 1").await?;
+
+    // core ids, and so the indices,
+    // aren't always contiguous
+    coretemp.file("temp6_label", "Core 4").await?;
+    coretemp.file("temp6_input", "45000").await?;
+    coretemp.file("temp6_crit_alarm", "1").await?;
+
+    coretemp.file("temp9_label", "Uncore").await?;
+    coretemp.file("temp9_input", "40000").await?;
+
+    let res = CoresProbe::new(coretemp.path()).probe().await?;
+
+    assert_eq!(res, Thermal {
+        packages: vec![
+            Package {
+                crit_alarm: false,
+                max_temp: 100000,
+                crit: 100000,
+                temp: 47000,
+                id: 1,
+            },
+        ],
+        sensors: vec![
+            Sensor {
+                crit_alarm: false,
+                label: "Uncore".to_string(),
+                max_temp: 0,
+                temp: 40000,
+                package: Some(1),
+                crit: 0,
+            },
+        ],
+        cores: vec![
+            Core {
+                crit_alarm: true,
+                max_temp: 0,
+                temp: 45000,
+                package: Some(1),
+                count: 4,
+                crit: 0,
+            },
+        ],
+    });
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_cores_io() -> crate::Any {
+    let err = CoresProbe::new("/tmp/not/a/file").probe().await
         .unwrap_err();
 
     assert!(matches!(err, CoreError::Io(_)));
@@ -253,17 +467,14 @@ fn info() -> Info {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_info_parse_float() -> crate::Any {
     let data = INFO_STR.replace("800.000", "abc");
 
-    super::point_env_file(
-        "INFO",
-        "/tmp/info",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
+
+    let cpuinfo = tmp.file("cpuinfo", &data).await?;
 
-    let err = Info::probe().await
+    let err = InfoProbe::new(cpuinfo).probe().await
         .unwrap_err();
 
     assert!(matches!(err, InfoError::ParseFloat(_)));
@@ -272,17 +483,14 @@ pub async fn test_probe_cpu_info_parse_float() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_info_missing_mhz() -> crate::Any {
     let data = INFO_STR.replace("cpu MHz", "placeholder");
 
-    super::point_env_file(
-        "INFO",
-        "/tmp/info",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Info::probe().await
+    let cpuinfo = tmp.file("cpuinfo", &data).await?;
+
+    let err = InfoProbe::new(cpuinfo).probe().await
         .unwrap_err();
 
     dbg!(&err);
@@ -293,17 +501,14 @@ pub async fn test_probe_cpu_info_missing_mhz() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_info_missing_cores() -> crate::Any {
     let data = INFO_STR.replace("cpu cores", "placeholder");
 
-    super::point_env_file(
-        "INFO",
-        "/tmp/info",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Info::probe().await
+    let cpuinfo = tmp.file("cpuinfo", &data).await?;
+
+    let err = InfoProbe::new(cpuinfo).probe().await
         .unwrap_err();
 
     dbg!(&err);
@@ -314,17 +519,14 @@ pub async fn test_probe_cpu_info_missing_cores() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_info_missing_siblings() -> crate::Any {
     let data = INFO_STR.replace("siblings", "placeholder");
 
-    super::point_env_file(
-        "INFO",
-        "/tmp/info",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Info::probe().await
+    let cpuinfo = tmp.file("cpuinfo", &data).await?;
+
+    let err = InfoProbe::new(cpuinfo).probe().await
         .unwrap_err();
 
     dbg!(&err);
@@ -335,17 +537,14 @@ pub async fn test_probe_cpu_info_missing_siblings() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_info_parse_int() -> crate::Any {
     let data = INFO_STR.replace("8", "abc");
 
-    super::point_env_file(
-        "INFO",
-        "/tmp/info",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Info::probe().await
+    let cpuinfo = tmp.file("cpuinfo", &data).await?;
+
+    let err = InfoProbe::new(cpuinfo).probe().await
         .unwrap_err();
 
     assert!(matches!(err, InfoError::ParseInt(_)));
@@ -354,15 +553,12 @@ pub async fn test_probe_cpu_info_parse_int() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_info_success() -> crate::Any {
-    super::point_env_file(
-        "INFO",
-        "/tmp/info",
-        INFO_STR,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
+
+    let cpuinfo = tmp.file("cpuinfo", INFO_STR).await?;
 
-    let res = Info::probe().await?;
+    let res = InfoProbe::new(cpuinfo).probe().await?;
 
     assert_eq!(res, info());
 
@@ -370,14 +566,98 @@ pub async fn test_probe_cpu_info_success() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_cpu_info_io() -> crate::Any {
-    std::env::set_var("INFO", "/tmp/not/a/file");
-
-    let err = Info::probe().await
+    let err = InfoProbe::new("/tmp/not/a/file").probe().await
         .unwrap_err();
 
     assert!(matches!(err, InfoError::Io(_)));
 
     Ok(())
 }
+
+async fn cpu(
+    tmp: &TempDir,
+    coretemp: impl AsRef<std::path::Path>,
+) -> anyhow::Result<CpuProbe> {
+    let stat = tmp.file("stat", USAGE_STR).await?;
+    let cpuinfo = tmp.file("cpuinfo", INFO_STR).await?;
+
+    Ok(CpuProbe {
+        usage: UsageProbe::new(stat),
+        cores: CoresProbe::new(coretemp.as_ref()),
+        info: InfoProbe::new(cpuinfo),
+    })
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_success() -> crate::Any {
+    let coretemp = setup("0", "Core 0").await?;
+
+    let res = cpu(&coretemp, coretemp.path()).await?
+        .probe().await?;
+
+    assert_eq!(res.sensor_status, Availability::Available);
+    assert_eq!(res.thermal.cores, cores());
+    assert_eq!(res.usage, Some(USAGE));
+    assert_eq!(res.info, Some(info()));
+    assert!(res.errors.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_sensors_unavailable() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let res = cpu(&tmp, "/tmp/not/a/dir").await?
+        .probe().await?;
+
+    assert_eq!(res.sensor_status, Availability::Unavailable);
+    assert_eq!(res.thermal, Thermal::default());
+    assert_eq!(res.usage, Some(USAGE));
+    assert_eq!(res.info, Some(info()));
+
+    assert_eq!(
+        res.errors.keys().collect::<Vec<_>>(),
+        vec![&Section::Thermal],
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_partial() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let mut probe = cpu(&tmp, "/tmp/not/a/dir").await?;
+
+    probe.info = InfoProbe::new("/tmp/not/a/file");
+
+    let res = probe.probe().await?;
+
+    assert_eq!(res.usage, Some(USAGE));
+    assert_eq!(res.info, None);
+
+    assert_eq!(
+        res.errors.keys().collect::<Vec<_>>(),
+        vec![&Section::Thermal, &Section::Info],
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_cpu_io() -> crate::Any {
+    let probe = CpuProbe {
+        usage: UsageProbe::new("/tmp/not/a/file"),
+        cores: CoresProbe::new("/tmp/not/a/dir"),
+        info: InfoProbe::new("/tmp/not/a/file"),
+    };
+
+    let err = probe.probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, CpuError::UsageError(UsageError::Io(_))));
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use monitor::probe::Probe;
+
+use crate::resources::disk::{Disk, DiskRatesProbe, Disks, DisksProbe, Error};
+
+use super::TempDir;
+
+// an older kernel without the discard and
+// flush counters, and a newer one with them
+const DISKSTATS_STR: &str = "   7       0 loop0 10 0 20 5 0 0 0 0 0 4 5
+   8       0 sda 1000 10 8000 500 400 20 4000 800 2 1000 1300 6 0 48 3 7 9
+";
+
+const DISKSTATS_NEXT_STR: &str = "   7       0 loop0 10 0 20 5 0 0 0 0 0 4 5
+   8       0 sda 1100 10 10000 700 500 20 6000 1100 1 1500 1800 6 0 48 3 7 9
+";
+
+fn sda() -> Disk {
+    Disk {
+        sectors_discarded: Some(48),
+        discards_merged: Some(0),
+        discard_time: Some(3),
+        flush_time: Some(9),
+        discards: Some(6),
+        flushes: Some(7),
+        weighted_io_time: 1300,
+        sectors_written: 4000,
+        writes_merged: 20,
+        reads_merged: 10,
+        sectors_read: 8000,
+        write_time: 800,
+        read_time: 500,
+        in_flight: 2,
+        io_time: 1000,
+        writes: 400,
+        reads: 1000,
+        minor: 0,
+        major: 8,
+        name: "sda".to_string(),
+    }
+}
+
+#[tokio::test]
+pub async fn test_probe_disks_success() -> crate::Any {
+    let tmp = TempDir::new().await?;
+    let file = tmp.file("diskstats", DISKSTATS_STR).await?;
+
+    let res = DisksProbe::new(file).probe().await?;
+
+    assert_eq!(res.disks.len(), 2);
+    assert_eq!(res.disks[1], sda());
+
+    assert_eq!(res.disks[0].name, "loop0");
+    assert_eq!(res.disks[0].discards, None);
+    assert_eq!(res.disks[0].flushes, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_disks_rate_since() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let prev = DisksProbe::new(tmp.file("prev", DISKSTATS_STR).await?)
+        .probe().await?;
+    let now = DisksProbe::new(tmp.file("now", DISKSTATS_NEXT_STR).await?)
+        .probe().await?;
+
+    let res = now.rate_since(&prev, Duration::from_secs(2));
+
+    let sda = &res.disks[1];
+
+    assert_eq!(sda.reads_per_sec, 50.0);
+    assert_eq!(sda.writes_per_sec, 50.0);
+    assert_eq!(sda.read_bytes_per_sec, 1000.0 * 512.0);
+    assert_eq!(sda.write_bytes_per_sec, 1000.0 * 512.0);
+    assert_eq!(sda.read_await, 2.0);
+    assert_eq!(sda.write_await, 3.0);
+    assert_eq!(sda.utilisation, 25.0);
+    assert_eq!(sda.in_flight, 1);
+
+    // an idle disk has no await
+    assert_eq!(res.disks[0].read_await, 0.0);
+    assert_eq!(res.disks[0].utilisation, 0.0);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_disks_rate_since_reset() -> crate::Any {
+    let prev = Disks {
+        disks: vec![sda()],
+    };
+
+    let mut reset = sda();
+    reset.reads = 0;
+
+    let added = Disk {
+        name: "sdb".to_string(),
+        ..sda()
+    };
+
+    let now = Disks {
+        disks: vec![reset, added],
+    };
+
+    // a reset disk and one that
+    // just showed up are left out
+    let res = now.rate_since(&prev, Duration::from_secs(1));
+
+    assert!(res.disks.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_disk_rates() -> crate::Any {
+    let tmp = TempDir::new().await?;
+    let file = tmp.file("diskstats", DISKSTATS_STR).await?;
+
+    let probe = DiskRatesProbe::new(DisksProbe::new(file));
+
+    // nothing happened between the first two samples
+    let res = probe.probe().await?;
+
+    assert_eq!(res.disks.len(), 2);
+    assert_eq!(res.disks[1].reads_per_sec, 0.0);
+
+    tmp.file("diskstats", DISKSTATS_NEXT_STR).await?;
+
+    let res = probe.probe().await?;
+
+    assert!(res.disks[1].reads_per_sec > 0.0);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_disks_invalid_line() -> crate::Any {
+    let tmp = TempDir::new().await?;
+    let file = tmp.file("diskstats", "8 0 sda 1 2 3").await?;
+
+    let err = DisksProbe::new(file).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::InvalidLine(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_disks_parse_int() -> crate::Any {
+    let tmp = TempDir::new().await?;
+    let file = tmp.file("diskstats", DISKSTATS_STR.replace("1000", "abc")).await?;
+
+    let err = DisksProbe::new(file).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::ParseInt(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_disks_io() -> crate::Any {
+    let err = DisksProbe::new("/tmp/not/a/file").probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::Io(_)));
+
+    Ok(())
+}
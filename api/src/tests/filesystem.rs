@@ -0,0 +1,202 @@
+use std::{
+    io,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use monitor::probe::Probe;
+
+use crate::resources::filesystem::{Error, FilesystemsProbe, Query, Usage};
+
+use super::TempDir;
+
+async fn setup() -> anyhow::Result<(TempDir, FilesystemsProbe)> {
+    let tmp = TempDir::new().await?;
+
+    tmp.dir("root/data disk").await?;
+    tmp.dir("root/proc").await?;
+
+    let mounts = tmp.file("mounts", "\
+/dev/sda1 / ext4 rw,relatime 0 0
+/dev/sdb1 /data\\040disk xfs ro,noatime 0 0
+proc /proc proc rw,nosuid 0 0
+server:/export /gone nfs rw 0 0
+").await?;
+
+    let probe = FilesystemsProbe::new(mounts, tmp.path().join("root"));
+
+    Ok((tmp, probe))
+}
+
+#[tokio::test]
+pub async fn test_probe_filesystems_success() -> crate::Any {
+    let (_tmp, probe) = setup().await?;
+
+    let res = probe.probe().await?;
+
+    assert_eq!(res.filesystems.len(), 4);
+
+    let root = &res.filesystems[0];
+
+    assert_eq!(root.device, "/dev/sda1");
+    assert_eq!(root.fstype, "ext4");
+    assert!(!root.read_only);
+    assert!(!root.pseudo);
+
+    let usage = root.usage.unwrap();
+
+    assert!(usage.size > 0);
+    assert_eq!(usage.used, usage.size - usage.free);
+    assert!(usage.available <= usage.free);
+    assert!((0.0..=100.0).contains(&usage.percent()));
+
+    let data = &res.filesystems[1];
+
+    assert_eq!(data.mountpoint.to_str(), Some("/data disk"));
+    assert!(data.usage.is_some());
+    assert!(data.read_only);
+
+    assert!(res.filesystems[2].pseudo);
+
+    // mountpoints that can't be
+    // stat'd have no usage
+    assert_eq!(res.filesystems[3].usage, None);
+    assert!(res.filesystems[3].error.is_some());
+    assert_eq!(root.error, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_filesystems_query() -> crate::Any {
+    let (_tmp, probe) = setup().await?;
+
+    let res = probe.probe().await?;
+
+    let query = Query {
+        pseudo: Some(false),
+        ..Default::default()
+    };
+
+    assert_eq!(res.clone().query(&query).filesystems.len(), 3);
+
+    let query = Query {
+        fstype: Some("xfs".to_string()),
+        ..Default::default()
+    };
+
+    let res = res.query(&query);
+
+    assert_eq!(res.filesystems.len(), 1);
+    assert_eq!(res.filesystems[0].device, "/dev/sdb1");
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_filesystems_invalid_line() -> crate::Any {
+    let tmp = TempDir::new().await?;
+    let mounts = tmp.file("mounts", "/dev/sda1 /\n").await?;
+
+    let err = FilesystemsProbe::new(mounts, "/").probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::InvalidLine(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_filesystems_io() -> crate::Any {
+    let err = FilesystemsProbe::new("/tmp/not/a/file", "/").probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::Io(_)));
+
+    Ok(())
+}
+
+/// How many times [`counted`] was called
+static STATS: AtomicUsize = AtomicUsize::new(0);
+
+fn counted(_: &Path) -> io::Result<Usage> {
+    STATS.fetch_add(1, Ordering::Relaxed);
+
+    // long enough for every stat
+    // of a probe to overlap
+    std::thread::sleep(Duration::from_millis(20));
+
+    Ok(Usage {
+        size: 100,
+        ..Default::default()
+    })
+}
+
+fn hung(_: &Path) -> io::Result<Usage> {
+    std::thread::sleep(Duration::from_millis(300));
+
+    Ok(Usage::default())
+}
+
+#[tokio::test]
+pub async fn test_probe_filesystems_duplicate() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let mounts = tmp.file("mounts", "\
+/dev/sda1 / ext4 rw,relatime 0 0
+tmpfs /dev/shm tmpfs rw,nosuid 0 0
+tmpfs /dev/shm tmpfs rw,nosuid,nodev 0 0
+").await?;
+
+    let mut probe = FilesystemsProbe::new(mounts, tmp.path());
+
+    probe.stat = counted;
+
+    let res = probe.probe().await?;
+
+    // stat'd once, shared by both
+    assert_eq!(STATS.load(Ordering::Relaxed), 2);
+    assert_eq!(res.filesystems.len(), 3);
+
+    for x in &res.filesystems {
+        assert_eq!(x.usage.map(|x| x.size), Some(100));
+        assert_eq!(x.error, None);
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_filesystems_timeout() -> crate::Any {
+    let tmp = TempDir::new().await?;
+    let mounts = tmp.file("mounts", "server:/export /gone nfs rw 0 0\n").await?;
+
+    let mut probe = FilesystemsProbe::new(mounts, tmp.path());
+
+    probe.timeout = Duration::from_millis(20);
+    probe.stat = hung;
+
+    let res = probe.probe().await?;
+
+    assert_eq!(res.filesystems[0].usage, None);
+    assert_eq!(res.filesystems[0].error.as_deref(), Some("statvfs timed out after 20ms"));
+
+    // the hung statvfs isn't
+    // started over meanwhile
+    let res = probe.probe().await?;
+
+    assert_eq!(
+        res.filesystems[0].error.as_deref(),
+        Some("still waiting on a statvfs from an earlier probe"),
+    );
+
+    // but is once it returns
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let res = probe.probe().await?;
+
+    assert_eq!(res.filesystems[0].error.as_deref(), Some("statvfs timed out after 20ms"));
+
+    Ok(())
+}
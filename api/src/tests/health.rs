@@ -0,0 +1,340 @@
+use std::time::Duration;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+};
+
+use monitor::{
+    health_check::{Health, Healthcheck},
+    hub::Hub,
+};
+
+use crate::{
+    health::{
+        self, Check, Checks, LinkDown, MemoryAvailable, RecentReboot, SwapUsed, Temperature,
+        Threshold,
+    },
+    resources::{
+        cpu::{Core, CoreError, Package, Thermal},
+        memory::{Memory, MemoryProbe},
+        network::{Info, Interface, InterfaceType, Network, OperState, Stats},
+        uptime::{Uptime, UptimeProbe},
+        Source,
+    },
+    routes,
+    state::Hubs,
+};
+
+use super::TempDir;
+
+// 5% of memory available
+const MEMINFO_STR: &str = "MemTotal: 1000 kB
+MemFree: 30 kB
+MemAvailable: 50 kB
+Buffers: 0 kB
+Cached: 0 kB
+SwapCached: 0 kB
+Active: 0 kB
+Inactive: 0 kB
+Active(anon): 0 kB
+Inactive(anon): 0 kB
+Active(file): 0 kB
+Inactive(file): 0 kB
+Unevictable: 0 kB
+Mlocked: 0 kB
+SwapTotal: 0 kB
+SwapFree: 0 kB
+Dirty: 0 kB
+";
+
+fn reason(health: Health) -> Option<String> {
+    match health {
+        Health::Unhealthy { reason } => reason,
+        Health::Healthy => None,
+    }
+}
+
+fn interface(name: &str, kind: InterfaceType, operstate: OperState) -> Interface {
+    Interface {
+        path: format!("/sys/class/net/{}", name).into(),
+        name: name.to_string(),
+        stats: Stats::default(),
+        info: Info {
+            address: "00:00:00:00:00:00".to_string(),
+            addresses: Vec::new(),
+            carrier_changes: None,
+            carrier: None,
+            duplex: None,
+            speed: 1000,
+            mtu: None,
+            operstate,
+            kind,
+        },
+    }
+}
+
+#[tokio::test]
+pub async fn test_health_memory() -> crate::Any {
+    let memory = Memory {
+        available: 50,
+        total: 1000,
+        swap_total: 200,
+        swap_free: 20,
+        ..Default::default()
+    };
+
+    let res = Threshold::<MemoryProbe>::check(&MemoryAvailable { min_percent: 10.0 }, &memory);
+
+    assert_eq!(reason(res).as_deref(), Some("only 5.0% of memory is available, under 10%"));
+
+    let res = Threshold::<MemoryProbe>::check(&SwapUsed { max_percent: 50.0 }, &memory);
+
+    assert_eq!(reason(res).as_deref(), Some("90.0% of swap is used, over 50%"));
+
+    let memory = Memory {
+        available: 500,
+        total: 1000,
+        ..Default::default()
+    };
+
+    // no swap is never too much swap
+    assert!(Threshold::<MemoryProbe>::check(&MemoryAvailable { min_percent: 10.0 }, &memory).is_healthy());
+    assert!(Threshold::<MemoryProbe>::check(&SwapUsed { max_percent: 50.0 }, &memory).is_healthy());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_health_temperature() -> crate::Any {
+    let threshold = Temperature {
+        margin: 5,
+    };
+
+    let mut thermal = Thermal {
+        packages: vec![Package {
+            temp: 60_000,
+            crit: 100_000,
+            ..Default::default()
+        }],
+        cores: vec![
+            Core {
+                temp: 94_000,
+                crit: 100_000,
+                package: Some(0),
+                count: 0,
+                ..Default::default()
+            },
+            Core {
+                temp: 96_000,
+                crit: 100_000,
+                package: Some(0),
+                count: 1,
+                ..Default::default()
+            },
+            Core {
+                temp: 96_000,
+                count: 2,
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        reason(threshold.check(&thermal)).as_deref(),
+        Some("core 1 of package 0 is at 96°C, within 5°C of its critical 100°C"),
+    );
+
+    thermal.packages[0].crit_alarm = true;
+
+    assert_eq!(
+        reason(threshold.check(&thermal)).as_deref(),
+        Some("package 0 hit its critical 100°C, core 1 of package 0 is at 96°C, within 5°C of its critical 100°C"),
+    );
+
+    // no coretemp is nothing to worry about
+    assert!(threshold.failed(&CoreError::NotFound).is_healthy());
+    assert!(!threshold.failed(&CoreError::InvalidLabel("x".to_string())).is_healthy());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_health_link() -> crate::Any {
+    let network = Network {
+        errors: [("wg0".to_string(), "bad".to_string())].into(),
+        interfaces: vec![
+            interface("eth0", InterfaceType::Physical, OperState::Up),
+            interface("eth1", InterfaceType::Physical, OperState::Down),
+            interface("br0", InterfaceType::Bridge, OperState::Down),
+            interface("lo", InterfaceType::Loopback, OperState::Unknown),
+        ],
+    };
+
+    let res = LinkDown::default().check(&network);
+
+    assert_eq!(reason(res).as_deref(), Some("eth1 is down"));
+
+    let res = LinkDown {
+        interfaces: vec!["eth0".to_string(), "br0".to_string(), "eth2".to_string()],
+    }
+    .check(&network);
+
+    assert_eq!(reason(res).as_deref(), Some("eth2 is missing, br0 is down"));
+
+    let res = LinkDown {
+        interfaces: vec!["eth0".to_string()],
+    }
+    .check(&network);
+
+    assert!(res.is_healthy());
+
+    let res = LinkDown {
+        interfaces: vec!["wg0".to_string()],
+    }
+    .check(&network);
+
+    assert_eq!(reason(res).as_deref(), Some("wg0 couldn't be read: bad"));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_health_uptime() -> crate::Any {
+    let threshold = RecentReboot {
+        min: Duration::from_secs(600),
+    };
+
+    let uptime = |secs| Uptime {
+        uptime: Duration::from_secs(secs),
+        idle: Duration::ZERO,
+    };
+
+    assert_eq!(reason(threshold.check(&uptime(200))).as_deref(), Some("rebooted 3m 20s ago, under 10m"));
+    assert!(threshold.check(&uptime(3600)).is_healthy());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_health_check_probe() -> crate::Any {
+    let tmp = TempDir::new().await?;
+    let uptime = tmp.file("uptime", "30.0 20.0").await?;
+
+    let threshold = RecentReboot {
+        min: Duration::from_secs(600),
+    };
+
+    let check = Check::new(Hub::new(UptimeProbe::new(uptime)), threshold);
+
+    assert_eq!(reason(check.health().await).as_deref(), Some("rebooted 30s ago, under 10m"));
+
+    let check = Check::new(Hub::new(UptimeProbe::new("/tmp/not/a/file")), threshold);
+
+    assert!(reason(check.health().await).is_some_and(|x| x.starts_with("failed to probe: ")));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_health_report() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    tmp.file("proc/uptime", "3600.0 20.0").await?;
+    tmp.file("proc/meminfo", MEMINFO_STR).await?;
+
+    let hubs = Hubs::from(&Source::new(tmp.path().join("proc"), tmp.path().join("sys")));
+
+    let config = health::Config {
+        memory: Some(MemoryAvailable { min_percent: 10.0 }),
+        uptime: Some(RecentReboot { min: Duration::from_secs(600) }),
+        temperature: None,
+        swap: None,
+        link: None,
+    };
+
+    let res = Checks::new(config, &hubs).report().await;
+
+    assert!(!res.healthy);
+    assert_eq!(res.checks.len(), 2);
+    assert!(res.checks["uptime"].healthy);
+    assert_eq!(
+        res.checks["memory"].reason.as_deref(),
+        Some("only 5.0% of memory is available, under 10%"),
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_health_route() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    tmp.file("proc/uptime", "3600.0 20.0").await?;
+    tmp.file("proc/meminfo", MEMINFO_STR).await?;
+
+    let hubs = Hubs::from(&Source::new(tmp.path().join("proc"), tmp.path().join("sys")));
+
+    let config = |min_percent| health::Config {
+        memory: Some(MemoryAvailable { min_percent }),
+        uptime: Some(RecentReboot { min: Duration::from_secs(600) }),
+        temperature: None,
+        swap: None,
+        link: None,
+    };
+
+    let res = routes::health(State(Checks::new(config(10.0), &hubs)))
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let body = axum::body::to_bytes(res.into_body(), usize::MAX).await?;
+    let report: serde_json::Value = serde_json::from_slice(&body)?;
+
+    assert_eq!(report["healthy"], false);
+    assert_eq!(report["checks"]["uptime"]["healthy"], true);
+
+    let res = routes::health(State(Checks::new(config(1.0), &hubs)))
+        .await
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_health_default_config() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    // a minute after boot, with a nic
+    // that isn't plugged in
+    tmp.file("proc/uptime", "60.0 20.0").await?;
+    tmp.file("proc/meminfo", MEMINFO_STR.replace("MemAvailable: 50", "MemAvailable: 500")).await?;
+    tmp.file("sys/class/net/eth0/operstate", "down").await?;
+    tmp.dir("sys/class/net/eth0/device").await?;
+
+    let hubs = Hubs::from(&Source::new(tmp.path().join("proc"), tmp.path().join("sys")));
+
+    let res = Checks::new(health::Config::default(), &hubs).report().await;
+
+    assert!(res.healthy);
+    assert!(!res.checks.contains_key("uptime"));
+    assert!(!res.checks.contains_key("link"));
+
+    // which only the opt in checks mind
+    let config = health::Config {
+        uptime: Some(RecentReboot { min: Duration::from_secs(600) }),
+        ..Default::default()
+    };
+
+    let res = Checks::new(config, &hubs).report().await;
+
+    assert!(!res.healthy);
+    assert!(!res.checks["uptime"].healthy);
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use crate::{
+    history::{self, Config, History, Query, Sample},
+    resources::loadavg::LoadAvgProbe,
+    store::Tier,
+};
+
+use super::TempDir;
+
+const LOADAVG_STR: &str = "0.50 0.25 0.10 1/100 42\n";
+
+fn history(retention: u64, resolution: u64) -> History<u64> {
+    let history = History::new(Config::new(
+        Duration::from_secs(retention),
+        Duration::from_secs(resolution),
+    ));
+
+    for x in 0..10 {
+        history.push(Sample::new(x * resolution * 1000, x));
+    }
+
+    history
+}
+
+fn values(samples: Vec<Sample<u64>>) -> Vec<u64> {
+    samples.into_iter()
+        .map(|x| x.value)
+        .collect()
+}
+
+fn query(since: Option<u64>, until: Option<u64>, step: Option<u64>) -> Query {
+    Query {
+        since,
+        until,
+        step,
+    }
+}
+
+#[tokio::test]
+pub async fn test_history_retention() -> crate::Any {
+    let res = history(30, 5);
+
+    assert_eq!(res.len(), 6);
+    assert_eq!(values(res.query(&Query::default()).await?), [4, 5, 6, 7, 8, 9]);
+
+    // a gap in the samples drops
+    // everything past the retention
+    res.push(Sample::new(100_000, 10));
+
+    assert_eq!(values(res.query(&Query::default()).await?), [10]);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_history_query() -> crate::Any {
+    let res = history(60, 1);
+
+    assert_eq!(values(res.query(&query(Some(2000), Some(5000), None)).await?), [2, 3, 4, 5]);
+    assert_eq!(values(res.query(&query(Some(7000), None, None)).await?), [7, 8, 9]);
+    assert_eq!(values(res.query(&query(None, None, Some(3000))).await?), [0, 3, 6, 9]);
+    assert_eq!(values(res.query(&query(Some(1000), Some(6000), Some(2500))).await?), [1, 3, 5]);
+    assert!(res.query(&query(Some(20_000), None, None)).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_history_disabled() -> crate::Any {
+    let res = history(0, 5);
+
+    assert!(res.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_history_record() -> crate::Any {
+    let tmp = TempDir::new().await?;
+    let file = tmp.file("loadavg", LOADAVG_STR).await?;
+
+    let res = History::new(Config::new(
+        Duration::from_secs(60),
+        Duration::from_millis(10),
+    ));
+
+    res.record(LoadAvgProbe::new(file));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let samples = res.query(&Query::default()).await?;
+
+    assert!(samples.len() >= 2);
+    assert!(samples.windows(2).all(|x| x[0].timestamp <= x[1].timestamp));
+    assert_eq!(samples[0].value.one, 0.5);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_history_persisted() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    // 10s in memory, then 1 sample
+    // every 10s for a minute on disk
+    let config = Config::new(Duration::from_secs(10), Duration::from_secs(1))
+        .with_tiers(vec![Tier::new(Duration::from_secs(10), Duration::from_secs(60))]);
+
+    let now = history::now() / 1000 * 1000;
+
+    let res = History::<u64>::open(config.clone(), tmp.path())?;
+
+    for x in 0..30 {
+        res.push(Sample::new(now - 29_000 + x * 1000, x));
+    }
+
+    assert_eq!(res.len(), 10);
+
+    // what shutting down does, leaving
+    // nothing for the drop to write
+    res.close();
+
+    drop(res);
+
+    // everything in memory is
+    // back after a restart
+    let res = History::<u64>::open(config, tmp.path())?;
+
+    assert_eq!(res.len(), 10);
+
+    let samples = res.query(&Query::default()).await?;
+
+    // past the memory, only the downsampled
+    // tier is left, one mean per window
+    assert!(samples.len() > 10 && samples.len() < 20);
+    assert!(samples.windows(2).all(|x| x[0].timestamp < x[1].timestamp));
+
+    let samples = values(samples);
+
+    assert_eq!(samples[samples.len() - 10..], [20, 21, 22, 23, 24, 25, 26, 27, 28, 29]);
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use monitor::probe::Probe;
+
+use crate::resources::{
+    cpu::{Core, CoreError, CoresProbe},
+    hwmon::{Channel, ChannelKind, Error, SensorsProbe},
+};
+
+use super::TempDir;
+
+async fn setup() -> anyhow::Result<TempDir> {
+    let sys = TempDir::new().await?;
+
+    // the class entries are symlinks
+    // to the actual devices
+    let coretemp = sys.dir("devices/coretemp.0/hwmon/hwmon3").await?;
+
+    sys.dir("class/hwmon").await?;
+
+    std::os::unix::fs::symlink(
+        &coretemp,
+        sys.path().join("class/hwmon/hwmon3"),
+    )?;
+
+    sys.file("devices/coretemp.0/hwmon/hwmon3/name", "coretemp\n").await?;
+    sys.file("devices/coretemp.0/hwmon/hwmon3/temp1_label", "Package id 0\n").await?;
+    sys.file("devices/coretemp.0/hwmon/hwmon3/temp1_input", "50000\n").await?;
+    sys.file("devices/coretemp.0/hwmon/hwmon3/temp1_max", "100000\n").await?;
+    sys.file("devices/coretemp.0/hwmon/hwmon3/temp1_crit", "100000\n").await?;
+    sys.file("devices/coretemp.0/hwmon/hwmon3/temp1_crit_alarm", "0\n").await?;
+    sys.file("devices/coretemp.0/hwmon/hwmon3/temp2_label", "Core 0\n").await?;
+    sys.file("devices/coretemp.0/hwmon/hwmon3/temp2_input", "47000\n").await?;
+    sys.file("devices/coretemp.0/hwmon/hwmon3/temp2_max", "100000\n").await?;
+    sys.file("devices/coretemp.0/hwmon/hwmon3/temp2_crit", "100000\n").await?;
+    sys.file("devices/coretemp.0/hwmon/hwmon3/temp2_crit_alarm", "1\n").await?;
+
+    // older drivers keep everything
+    // in the device directory
+    sys.file("class/hwmon/hwmon5/device/name", "it8728\n").await?;
+    sys.file("class/hwmon/hwmon5/device/fan1_input", "1200\n").await?;
+    sys.file("class/hwmon/hwmon5/device/fan1_min", "300\n").await?;
+    sys.file("class/hwmon/hwmon5/device/fan1_alarm", "0\n").await?;
+    sys.file("class/hwmon/hwmon5/device/power1_average", "15000000\n").await?;
+    sys.file("class/hwmon/hwmon5/device/uevent", "").await?;
+
+    // a device removed while probing
+    // leaves its link dangling
+    std::os::unix::fs::symlink(
+        sys.path().join("devices/gone"),
+        sys.path().join("class/hwmon/hwmon7"),
+    )?;
+
+    Ok(sys)
+}
+
+#[tokio::test]
+pub async fn test_probe_hwmon_success() -> crate::Any {
+    let sys = setup().await?;
+
+    let sensors = SensorsProbe::new(sys.path().join("class/hwmon"))
+        .probe().await?;
+
+    let [coretemp, it8728] = sensors.chips.try_into()
+        .expect("expected exactly two chips");
+
+    assert_eq!(coretemp.name, "coretemp");
+    assert_eq!(it8728.name, "it8728");
+
+    assert_eq!(it8728.path, sys.path().join("class/hwmon/hwmon5/device"));
+
+    assert_eq!(coretemp.channels, vec![
+        Channel {
+            kind: ChannelKind::Temp,
+            label: Some("Package id 0".to_string()),
+            emergency: None,
+            input: Some(50000),
+            lcrit: None,
+            crit: Some(100000),
+            min: None,
+            max: Some(100000),
+            alarms: vec![],
+            index: 1,
+        },
+        Channel {
+            kind: ChannelKind::Temp,
+            label: Some("Core 0".to_string()),
+            emergency: None,
+            input: Some(47000),
+            lcrit: None,
+            crit: Some(100000),
+            min: None,
+            max: Some(100000),
+            alarms: vec!["crit".to_string()],
+            index: 2,
+        },
+    ]);
+
+    assert_eq!(it8728.channels, vec![
+        Channel {
+            kind: ChannelKind::Fan,
+            label: None,
+            emergency: None,
+            input: Some(1200),
+            lcrit: None,
+            crit: None,
+            min: Some(300),
+            max: None,
+            alarms: vec![],
+            index: 1,
+        },
+        Channel {
+            kind: ChannelKind::Power,
+            label: None,
+            emergency: None,
+            input: Some(15000000),
+            lcrit: None,
+            crit: None,
+            min: None,
+            max: None,
+            alarms: vec![],
+            index: 1,
+        },
+    ]);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_hwmon_find() -> crate::Any {
+    let sys = setup().await?;
+
+    let probe = SensorsProbe::new(sys.path().join("class/hwmon"));
+
+    assert_eq!(
+        probe.find("coretemp").await?,
+        vec![sys.path().join("class/hwmon/hwmon3")],
+    );
+
+    assert!(probe.find("k10temp").await?.is_empty());
+
+    let probe = SensorsProbe::new("/tmp/not/a/dir");
+
+    assert!(probe.find("coretemp").await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_hwmon_cores_discovery() -> crate::Any {
+    let sys = setup().await?;
+
+    let probe = CoresProbe {
+        sensors: SensorsProbe::new(sys.path().join("class/hwmon")),
+        dir: None,
+    };
+
+    assert_eq!(probe.probe().await?.cores, vec![
+        Core {
+            crit_alarm: true,
+            max_temp: 100000,
+            crit: 100000,
+            temp: 47000,
+            package: Some(0),
+            count: 0,
+        },
+    ]);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_hwmon_cores_not_found() -> crate::Any {
+    let sys = TempDir::new().await?;
+
+    let probe = CoresProbe {
+        sensors: SensorsProbe::new(sys.dir("class/hwmon").await?),
+        dir: None,
+    };
+
+    let err = probe.probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, CoreError::NotFound));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_hwmon_io() -> crate::Any {
+    let err = SensorsProbe::new("/tmp/not/a/dir").probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::Io(_)));
+
+    Ok(())
+}
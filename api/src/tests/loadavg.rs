@@ -0,0 +1,119 @@
+use monitor::probe::Probe;
+
+use crate::resources::loadavg::{Error, LoadAvg, LoadAvgProbe};
+
+const LOADAVG_STR: &str = "0.50 1.25 2.00 3/456 7890";
+
+const LOADAVG: LoadAvg = LoadAvg {
+    one: 0.5,
+    five: 1.25,
+    fifteen: 2.0,
+    running: 3,
+    total: 456,
+    last_pid: 7890,
+};
+
+#[tokio::test]
+pub async fn test_probe_loadavg_parse_float() -> crate::Any {
+    let data = LOADAVG_STR.replace("1.25", "abc");
+
+    let tmp = super::TempDir::new().await?;
+
+    let loadavg = tmp.file("loadavg", &data).await?;
+
+    let err = LoadAvgProbe::new(loadavg).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::ParseFloat(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_loadavg_parse_int() -> crate::Any {
+    let data = LOADAVG_STR.replace("7890", "abc");
+
+    let tmp = super::TempDir::new().await?;
+
+    let loadavg = tmp.file("loadavg", &data).await?;
+
+    let err = LoadAvgProbe::new(loadavg).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::ParseInt(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_loadavg_invalid() -> crate::Any {
+    let data = format!("{} 30", LOADAVG_STR);
+
+    let tmp = super::TempDir::new().await?;
+
+    let loadavg = tmp.file("loadavg", &data).await?;
+
+    let err = LoadAvgProbe::new(loadavg).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::InvalidLoadAvg));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_loadavg_invalid_tasks() -> crate::Any {
+    let data = LOADAVG_STR.replace("3/456", "3");
+
+    let tmp = super::TempDir::new().await?;
+
+    let loadavg = tmp.file("loadavg", &data).await?;
+
+    let err = LoadAvgProbe::new(loadavg).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::InvalidTasks));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_loadavg_success() -> crate::Any {
+    let data = LOADAVG_STR;
+
+    let tmp = super::TempDir::new().await?;
+
+    let loadavg = tmp.file("loadavg", data).await?;
+
+    let loadavg = LoadAvgProbe::new(loadavg).probe().await?;
+
+    assert_eq!(loadavg, LOADAVG);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_loadavg_empty() -> crate::Any {
+    let data = "";
+
+    let tmp = super::TempDir::new().await?;
+
+    let loadavg = tmp.file("loadavg", data).await?;
+
+    let err = LoadAvgProbe::new(loadavg).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::Empty));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_loadavg_io() -> crate::Any {
+    let err = LoadAvgProbe::new("/tmp/not/a/file").probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::Io(_)));
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use monitor::probe::Probe;
 
-use crate::resources::memory::{Error, Memory};
+use crate::resources::memory::{Error, Memory, MemoryProbe};
 
 const MEMINFO_STR: &str = 
 "Inactive(anon):     1 kB\n\
@@ -49,17 +49,14 @@ const MEMINFO: Memory = Memory {
 use tokio_test::*;
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_mem_invalid_format() -> crate::Any {
     let data = format!("{}Dirty:     17 mB", MEMINFO_STR);
 
-    super::point_env_file(
-        "MEMINFO",
-        "/tmp/meminfo",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Memory::probe().await
+    let meminfo = tmp.file("meminfo", &data).await?;
+
+    let err = MemoryProbe::new(meminfo).probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::InvalidFormat(_)));
@@ -68,17 +65,15 @@ pub async fn test_probe_mem_invalid_format() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
+#[allow(clippy::useless_format)]
 pub async fn test_probe_mem_missing_field() -> crate::Any {
     let data = format!("{}", MEMINFO_STR);
 
-    super::point_env_file(
-        "MEMINFO",
-        "/tmp/meminfo",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
+
+    let meminfo = tmp.file("meminfo", &data).await?;
 
-    let err = Memory::probe().await
+    let err = MemoryProbe::new(meminfo).probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::MissingField(_)));
@@ -87,17 +82,14 @@ pub async fn test_probe_mem_missing_field() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_mem_missing_colon() -> crate::Any {
     let data = format!("{}Dirty     17 kB", MEMINFO_STR);
 
-    super::point_env_file(
-        "MEMINFO",
-        "/tmp/meminfo",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Memory::probe().await
+    let meminfo = tmp.file("meminfo", &data).await?;
+
+    let err = MemoryProbe::new(meminfo).probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::MissingColon(_)));
@@ -106,17 +98,14 @@ pub async fn test_probe_mem_missing_colon() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_mem_value_empty() -> crate::Any {
     let data = format!("{}Dirty:", MEMINFO_STR);
 
-    super::point_env_file(
-        "MEMINFO",
-        "/tmp/meminfo",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
+
+    let meminfo = tmp.file("meminfo", &data).await?;
 
-    let err = Memory::probe().await
+    let err = MemoryProbe::new(meminfo).probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::ValueEmpty(_)));
@@ -125,17 +114,14 @@ pub async fn test_probe_mem_value_empty() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_mem_parse_int() -> crate::Any {
     let data = format!("{}Dirty:     abc kB", MEMINFO_STR);
 
-    super::point_env_file(
-        "MEMINFO",
-        "/tmp/meminfo",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Memory::probe().await
+    let meminfo = tmp.file("meminfo", &data).await?;
+
+    let err = MemoryProbe::new(meminfo).probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::ParseInt(_)));
@@ -144,17 +130,14 @@ pub async fn test_probe_mem_parse_int() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_mem_key_empty() -> crate::Any {
     let data = format!("{}:     17 kB", MEMINFO_STR);
 
-    super::point_env_file(
-        "MEMINFO",
-        "/tmp/meminfo",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
+
+    let meminfo = tmp.file("meminfo", &data).await?;
 
-    let err = Memory::probe().await
+    let err = MemoryProbe::new(meminfo).probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::KeyEmpty(_)));
@@ -163,17 +146,14 @@ pub async fn test_probe_mem_key_empty() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_mem_success() -> crate::Any {
     let data = format!("{}Dirty:    17 kB", MEMINFO_STR);
 
-    super::point_env_file(
-        "MEMINFO",
-        "/tmp/meminfo",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let mem = Memory::probe().await?;
+    let meminfo = tmp.file("meminfo", &data).await?;
+
+    let mem = MemoryProbe::new(meminfo).probe().await?;
 
     assert_eq!(mem, MEMINFO);
 
@@ -181,11 +161,8 @@ pub async fn test_probe_mem_success() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_mem_io() -> crate::Any {
-    std::env::set_var("MEMINFO", "/tmp/not/a/file");
-
-    let err = Memory::probe().await
+    let err = MemoryProbe::new("/tmp/not/a/file").probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::Io(_)));
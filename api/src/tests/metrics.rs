@@ -0,0 +1,162 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use crate::{
+    metrics::Exposition,
+    resources::{
+        cpu::{Core, Thermal, Usage},
+        hwmon::{Channel, ChannelKind, Chip, Sensors},
+        network::{Info, Interface, InterfaceType, Network, OperState, Stats},
+        socket::{Sockets, TcpState},
+        uptime::Uptime,
+    },
+};
+
+#[tokio::test]
+pub async fn test_metrics_render() -> crate::Any {
+    let mut out = Exposition::new();
+
+    out.counter("requests_total", "Requests.", &[("path", "/a")], 1.0);
+    out.gauge("up", "Up.", &[], 1.0);
+    out.counter("requests_total", "Requests.", &[("path", "a \"b\"\\\n")], 2.5);
+
+    assert_eq!(out.render(), "\
+# HELP monitor_requests_total Requests.
+# TYPE monitor_requests_total counter
+monitor_requests_total{path=\"/a\"} 1
+monitor_requests_total{path=\"a \\\"b\\\"\\\\\\n\"} 2.5
+# HELP monitor_up Up.
+# TYPE monitor_up gauge
+monitor_up 1
+");
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_metrics_network() -> crate::Any {
+    let network = Network {
+        errors: BTreeMap::from([("wg0".to_string(), "bad".to_string())]),
+        interfaces: vec![
+            Interface {
+                path: "/sys/class/net/eth0".into(),
+                name: "eth0".to_string(),
+                stats: Stats {
+                    rx_errors: Some(3),
+                    rx_bytes: 100,
+                    ..Default::default()
+                },
+                info: Info {
+                    address: "00:00:00:00:00:00".to_string(),
+                    kind: InterfaceType::Physical,
+                    operstate: OperState::Up,
+                    addresses: Vec::new(),
+                    carrier_changes: None,
+                    carrier: None,
+                    duplex: None,
+                    speed: 1000,
+                    mtu: None,
+                },
+            },
+        ],
+    };
+
+    let out = Exposition::new()
+        .add(&network)
+        .render();
+
+    assert!(out.contains("# TYPE monitor_network_receive_bytes_total counter\n"));
+    assert!(out.contains("monitor_network_receive_bytes_total{interface=\"eth0\"} 100\n"));
+    assert!(out.contains("monitor_network_receive_errors_total{interface=\"eth0\"} 3\n"));
+    assert!(out.contains("monitor_network_speed_bytes{interface=\"eth0\"} 125000000\n"));
+    assert!(out.contains("monitor_network_up{interface=\"eth0\"} 1\n"));
+    assert!(out.contains("monitor_network_interface_error{interface=\"wg0\"} 1\n"));
+
+    // counters the driver doesn't keep are left out
+    assert!(!out.contains("monitor_network_transmit_errors_total"));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_metrics_resources() -> crate::Any {
+    let thermal = Thermal {
+        cores: vec![
+            Core {
+                crit_alarm: false,
+                max_temp: 100000,
+                temp: 47500,
+                crit: 100000,
+                package: Some(0),
+                count: 2,
+            },
+        ],
+        ..Default::default()
+    };
+
+    let uptime = Uptime {
+        uptime: Duration::from_millis(1500),
+        idle: Duration::from_secs(3),
+    };
+
+    let sockets = Sockets {
+        states: BTreeMap::from([(TcpState::CloseWait, 4)]),
+        ..Default::default()
+    };
+
+    let usage = Usage {
+        user: 300,
+        guest: 200,
+        ..Default::default()
+    };
+
+    let sensors = Sensors {
+        chips: vec![
+            Chip {
+                path: "/sys/class/hwmon/hwmon2".into(),
+                name: "nvme".to_string(),
+                channels: vec![
+                    Channel {
+                        kind: ChannelKind::Temp,
+                        label: Some("Composite".to_string()),
+                        alarms: vec!["crit".to_string()],
+                        input: Some(85850),
+                        crit: Some(84850),
+                        emergency: None,
+                        lcrit: None,
+                        min: None,
+                        max: None,
+                        index: 1,
+                    },
+                ],
+            },
+        ],
+    };
+
+    let out = Exposition::new()
+        .add(&thermal)
+        .add(&uptime)
+        .add(&sockets)
+        .add(&usage)
+        .add(&sensors)
+        .render();
+
+    assert!(out.contains("monitor_cpu_core_temperature_celsius{package=\"0\",core=\"2\"} 47.5\n"));
+    assert!(out.contains("# TYPE monitor_uptime_seconds_total counter\nmonitor_uptime_seconds_total 1.5\n"));
+    assert!(out.contains("monitor_tcp_connections{state=\"close_wait\"} 4\n"));
+
+    // guest time is in user already, so
+    // it's not another cpu_seconds mode
+    let ticks = crate::resources::process::ticks() as f64;
+
+    assert!(out.contains(&format!("monitor_cpu_seconds_total{{mode=\"user\"}} {}\n", 300.0 / ticks)));
+    assert!(out.contains(&format!("monitor_cpu_guest_seconds_total{{mode=\"user\"}} {}\n", 200.0 / ticks)));
+    assert!(!out.contains("mode=\"guest"));
+
+    let labels = "chip=\"hwmon2\",chip_name=\"nvme\",sensor=\"temp1\",label=\"Composite\"";
+
+    assert!(out.contains(&format!("monitor_hwmon_temp_celsius{{{}}} 85.85\n", labels)));
+    assert!(out.contains(&format!("monitor_hwmon_temp_crit_celsius{{{}}} 84.85\n", labels)));
+    assert!(out.contains(&format!("monitor_hwmon_alarm{{{},alarm=\"crit\"}} 1\n", labels)));
+
+    Ok(())
+}
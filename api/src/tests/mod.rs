@@ -3,56 +3,97 @@ mod network;
 #[cfg(test)]
 mod memory;
 #[cfg(test)]
+mod metrics;
+#[cfg(test)]
+mod disk;
+#[cfg(test)]
+mod filesystem;
+#[cfg(test)]
+mod health;
+#[cfg(test)]
+mod history;
+#[cfg(test)]
 mod uptime;
 #[cfg(test)]
+mod loadavg;
+#[cfg(test)]
+mod pressure;
+#[cfg(test)]
+mod process;
+#[cfg(test)]
+mod socket;
+#[cfg(test)]
+mod store;
+#[cfg(test)]
+mod hwmon;
+#[cfg(test)]
 mod cpu;
 
-use tokio::{fs::{create_dir, File}, io::AsyncWriteExt};
-
-pub async fn point_env_dir<S, A>(
-    env: S,
-    path: S,
-    data: A,
-) -> crate::Any
-where
-    S: AsRef<str>,
-    A: AsRef<[S]>,
-{
-    let _ = create_dir(path.as_ref()).await;
-
-    for v in data.as_ref() {
-        let _ = create_dir(
-            format!("{}/{}", path.as_ref(), v.as_ref())
-        ).await;
-    }
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    path::{Path, PathBuf},
+};
 
-    std::env::set_var(
-        env.as_ref(),
-        path.as_ref(),
-    );
+use tokio::{fs::{create_dir_all, File}, io::AsyncWriteExt};
 
-    Ok(())
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory unique to a single test,
+/// so tests can run in parallel. It is
+/// removed once dropped.
+pub struct TempDir(PathBuf);
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
 }
 
-pub async fn point_env_file<S>(
-    env: S,
-    path: S,
-    data: S,
-) -> crate::Any
-where
-    S: AsRef<str>,
-{
-    let mut file = File::create(
-        path.as_ref(),
-    ).await?;
-
-    file.write_all(data.as_ref().as_bytes())
-        .await?;
-
-    std::env::set_var(
-        env.as_ref(),
-        path.as_ref(),
-    );
-
-    Ok(())
+impl TempDir {
+    pub async fn new() -> anyhow::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "monitor-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed),
+        ));
+
+        create_dir_all(&path).await?;
+
+        Ok(Self(path))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub async fn dir(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> anyhow::Result<PathBuf> {
+        let path = self.0.join(path);
+
+        create_dir_all(&path).await?;
+
+        Ok(path)
+    }
+
+    pub async fn file(
+        &self,
+        path: impl AsRef<Path>,
+        data: impl AsRef<str>,
+    ) -> anyhow::Result<PathBuf> {
+        let path = self.0.join(path);
+
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).await?;
+        }
+
+        let mut file = File::create(&path)
+            .await?;
+
+        file.write_all(data.as_ref().as_bytes())
+            .await?;
+
+        Ok(path)
+    }
 }
@@ -1,17 +1,13 @@
-use std::fs::remove_dir_all;
+use std::{collections::BTreeMap, path::Path, time::Duration};
 
-use tokio::{fs::File, io::AsyncWriteExt};
 use monitor::probe::Probe;
 
-use crate::resources::network::{Error, Info, Interface, Network, OperState, Stats};
+use crate::resources::network::{
+    Address, Duplex, Error, EventKind, NetworkEvent, Info, Interface, InterfaceType, Network, NetworkProbe,
+    NetworkRatesProbe, OperState, Query, Stats,
+};
 
-struct SetDown;
-
-impl Drop for SetDown {
-    fn drop(&mut self) {
-        remove_dir_all("/tmp/net").unwrap();
-    }
-}
+use super::TempDir;
 
 async fn setup(
     operstate: OperState,
@@ -19,65 +15,46 @@ async fn setup(
     miss_stat: bool,
     miss_info: bool,
     null_speed: bool,
-) -> anyhow::Result<SetDown> {
-    super::point_env_dir(
-        "NET",
-        "/tmp/net",
-        [
-            "testing",
-            "testing/statistics"
-        ]
-    ).await?;
+) -> anyhow::Result<TempDir> {
+    let net = TempDir::new().await?;
 
-    async fn write_to(
-        data: impl AsRef<str>,
-        path: impl AsRef<str>
-    ) -> crate::Any {
-        let mut file = File::create(path.as_ref())
-            .await?;
+    net.dir("testing/statistics").await?;
 
-        file.write_all(
-            data.as_ref().as_bytes()
-        ).await?;
+    let statistics = "testing/statistics";
+    let info = "testing";
 
-        Ok(())
-    }
-
-    let statistics = "/tmp/net/testing/statistics";
-    let info = "/tmp/net/testing";
-
-    write_to(
-        stat,
+    net.file(
         format!("{statistics}/rx_packets"),
+        stat,
     ).await?;
 
     if !miss_stat {
-        write_to(
-            "2",
+        net.file(
             format!("{statistics}/tx_packets"),
+            "2",
         ).await?;
     }
 
-    write_to(
-        "3",
+    net.file(
         format!("{statistics}/rx_bytes"),
+        "3",
     ).await?;
 
-    write_to(
-        "4",
+    net.file(
         format!("{statistics}/tx_bytes"),
+        "4",
     ).await?;
 
     if !miss_info {
-        write_to(
-            "testaddr",
+        net.file(
             format!("{info}/address"),
+            "testaddr",
         ).await?;
     }
 
-    write_to(
-        operstate.to_string(),
+    net.file(
         format!("{info}/operstate"),
+        operstate.to_string(),
     ).await?;
 
     let speed = if null_speed {
@@ -86,44 +63,62 @@ async fn setup(
         "1000"
     };
 
-    write_to(
-        speed,
+    net.file(
         format!("{info}/speed"),
+        speed,
     ).await?;
 
-    Ok(SetDown)
+    Ok(net)
 }
 
-fn network() -> Network {
+fn network(net: &Path) -> Network {
     let interfaces = vec![
         Interface {
-            path: "/tmp/net/testing".into(),
+            path: net.join("testing"),
             name: "testing".to_string(),
             stats: Stats {
                 rx_packets: 1,
                 tx_packets: 2,
                 rx_bytes: 3,
                 tx_bytes: 4,
+                ..Default::default()
             },
             info: Info {
                 address: "testaddr".to_string(),
+                kind: InterfaceType::Virtual,
                 operstate: OperState::Up,
+                addresses: Vec::new(),
+                carrier_changes: None,
+                carrier: None,
+                duplex: None,
                 speed: 1000,
+                mtu: None,
             },
         },
     ];
 
     Network {
+        errors: BTreeMap::new(),
         interfaces,
     }
 }
 
+/// Probes a network whose only interface
+/// is bad, returning its error
+async fn error(net: &TempDir) -> anyhow::Result<String> {
+    let nw = NetworkProbe::new(net.path(), "/tmp/not/a/dir").probe().await?;
+
+    assert!(nw.interfaces.is_empty());
+
+    Ok(nw.errors.get("testing").cloned().unwrap_or_default())
+}
+
 #[tokio::test]
-#[serial_test::serial]
+#[allow(clippy::get_first)]
 pub async fn test_probe_network_null_speed_link_down() -> crate::Any {
-    let _setdown = setup(OperState::Down, "1", false, false, true).await?;
+    let net = setup(OperState::Down, "1", false, false, true).await?;
 
-    let nw = Network::probe().await?;
+    let nw = NetworkProbe::new(net.path(), "/tmp/not/a/dir").probe().await?;
 
     let testing = nw.interfaces.get(0)
         .expect("testing interface doesn't exist");
@@ -134,78 +129,418 @@ pub async fn test_probe_network_null_speed_link_down() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_network_null_speed_link_up() -> crate::Any {
-    let _setdown = setup(OperState::Up, "1", false, false, true).await?;
+    let net = setup(OperState::Up, "1", false, false, true).await?;
 
-    let err = Network::probe().await
-        .unwrap_err();
+    assert!(error(&net).await?.contains("invalidstate"));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_network_unknown_speed() -> crate::Any {
+    // virtual devices are up with
+    // a speed the kernel shows as -1
+    let net = setup(OperState::Up, "1", false, false, false).await?;
+
+    net.file("testing/speed", "-1").await?;
+
+    let nw = NetworkProbe::new(net.path(), "/tmp/not/a/dir").probe().await?;
+
+    assert_eq!(nw.interfaces[0].info.speed, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_network_bad_interface() -> crate::Any {
+    let net = setup(OperState::Up, "1", false, false, false).await?;
+
+    // a device without any statistics
+    net.file("wg0/operstate", "unknown").await?;
+
+    let nw = NetworkProbe::new(net.path(), "/tmp/not/a/dir").probe().await?;
+
+    assert_eq!(nw.interfaces, network(net.path()).interfaces);
+    assert_eq!(nw.errors.keys().collect::<Vec<_>>(), vec!["wg0"]);
+
+    let rates = nw.rate_since(&nw, Duration::from_secs(1));
 
-    assert!(matches!(err, Error::InvalidLinkState { .. }));
+    assert_eq!(rates.interfaces.len(), 1);
+    assert_eq!(rates.errors, nw.errors);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_network_operstate() -> crate::Any {
+    for state in [
+        OperState::NotPresent,
+        OperState::LowerLayerDown,
+        OperState::Testing,
+        OperState::Dormant,
+    ] {
+        let net = setup(state.clone(), "1", false, false, true).await?;
+
+        let nw = NetworkProbe::new(net.path(), "/tmp/not/a/dir").probe().await?;
+
+        assert_eq!(nw.interfaces[0].info.operstate, state);
+        assert_eq!(nw.interfaces[0].info.speed, 0);
+    }
 
     Ok(())
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_network_missing_stat() -> crate::Any {
-    let _setdown = setup(OperState::Up, "1", true, false, false).await?;
+    let net = setup(OperState::Up, "1", true, false, false).await?;
 
-    let err = Network::probe().await
-        .unwrap_err();
+    assert!(error(&net).await?.contains("stat 'tx_packets' not found"));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_network_optional_stats() -> crate::Any {
+    let net = setup(OperState::Up, "1", false, false, false).await?;
+
+    net.file("testing/statistics/rx_errors", "5").await?;
+    net.file("testing/statistics/collisions", "0").await?;
+
+    let nw = NetworkProbe::new(net.path(), "/tmp/not/a/dir").probe().await?;
+
+    let stats = &nw.interfaces[0].stats;
+
+    assert_eq!(stats.rx_errors, Some(5));
+    assert_eq!(stats.collisions, Some(0));
+
+    // the driver doesn't keep these
+    assert_eq!(stats.tx_errors, None);
+    assert_eq!(stats.rx_crc_errors, None);
 
-    assert!(matches!(err, Error::MissingStat { .. }));
+    net.file("testing/statistics/tx_dropped", "abc").await?;
+
+    assert!(error(&net).await?.contains("tx_dropped"));
 
     Ok(())
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_network_missing_info() -> crate::Any {
-    let _setdown = setup(OperState::Up, "1", false, true, false).await?;
-
-    let err = Network::probe().await
-        .unwrap_err();
+    let net = setup(OperState::Up, "1", false, true, false).await?;
 
-    assert!(matches!(err, Error::MissingInfo { .. }));
+    assert!(error(&net).await?.contains("info 'address' not found"));
 
     Ok(())
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_network_success() -> crate::Any {
-    let _setdown = setup(OperState::Up, "1", false, false, false).await?;
+    let net = setup(OperState::Up, "1", false, false, false).await?;
 
-    let nw = Network::probe().await?;
+    let nw = NetworkProbe::new(net.path(), "/tmp/not/a/dir").probe().await?;
 
-    assert_eq!(nw, network());
+    assert_eq!(nw, network(net.path()));
 
     Ok(())
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_network_parse() -> crate::Any {
-    let _setdown = setup(OperState::Up, "abc", false, false, false).await?;
-
-    let err = Network::probe().await
-        .unwrap_err();
+    let net = setup(OperState::Up, "abc", false, false, false).await?;
 
-    assert!(matches!(err, Error::ParseError { .. }));
+    assert!(error(&net).await?.contains("failed to parse stat"));
 
     Ok(())
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_network_io() -> crate::Any {
-    std::env::set_var("NET", "/tmp/not/a/dir");
-
-    let err = Network::probe().await
+    let err = NetworkProbe::new("/tmp/not/a/dir", "/tmp/not/a/dir").probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::Io(_)));
 
     Ok(())
 }
+
+fn stats(rx_bytes: usize, tx_bytes: usize, rx_packets: usize, tx_packets: usize) -> Network {
+    let mut nw = network(Path::new("/sys/class/net"));
+
+    nw.interfaces[0].stats = Stats {
+        rx_packets,
+        tx_packets,
+        rx_bytes,
+        tx_bytes,
+        ..Default::default()
+    };
+
+    nw
+}
+
+#[tokio::test]
+pub async fn test_network_rate_since() -> crate::Any {
+    let prev = stats(0, 1000, 10, 20);
+    let now = stats(25_000_000, 3000, 30, 20);
+
+    let res = now.rate_since(&prev, Duration::from_secs(2));
+
+    let testing = &res.interfaces[0];
+
+    assert_eq!(testing.rx_bytes_per_sec, 12_500_000.0);
+    assert_eq!(testing.tx_bytes_per_sec, 1000.0);
+    assert_eq!(testing.rx_packets_per_sec, 10.0);
+    assert_eq!(testing.tx_packets_per_sec, 0.0);
+
+    // 100 Mbit/s of a 1000 Mbit/s link
+    assert_eq!(testing.rx_utilisation, Some(10.0));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_network_rate_since_unknown_speed() -> crate::Any {
+    let prev = stats(0, 0, 0, 0);
+    let mut now = stats(100, 100, 1, 1);
+
+    now.interfaces[0].info.speed = 0;
+
+    let res = now.rate_since(&prev, Duration::from_secs(1));
+
+    assert_eq!(res.interfaces[0].rx_utilisation, None);
+    assert_eq!(res.interfaces[0].tx_utilisation, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_network_rate_since_wraparound() -> crate::Any {
+    // a 32 bit counter wrapping around
+    let prev = stats(u32::MAX as usize - 99, 0, 0, 0);
+    let now = stats(100, 0, 0, 0);
+
+    let res = now.rate_since(&prev, Duration::from_secs(1));
+
+    assert_eq!(res.interfaces[0].rx_bytes_per_sec, 200.0);
+
+    // a counter that was reset leaves
+    // the interface out until the next
+    let prev = stats(5000, 0, 0, 0);
+
+    let res = now.rate_since(&prev, Duration::from_secs(1));
+
+    assert!(res.interfaces.is_empty());
+
+    // even from the upper half of 32 bits,
+    // as long as it's nowhere near wrapping
+    let prev = stats(u32::MAX as usize / 2 + 100, 0, 0, 0);
+
+    let res = now.rate_since(&prev, Duration::from_secs(1));
+
+    assert!(res.interfaces.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_network_rates() -> crate::Any {
+    let net = setup(OperState::Up, "1", false, false, false).await?;
+
+    let probe = NetworkRatesProbe::new(NetworkProbe::new(net.path(), "/tmp/not/a/dir"));
+
+    // nothing was sent between the first two samples
+    let res = probe.probe().await?;
+
+    assert_eq!(res.interfaces.len(), 1);
+    assert_eq!(res.interfaces[0].rx_packets_per_sec, 0.0);
+    assert_eq!(res.interfaces[0].rx_utilisation, Some(0.0));
+
+    net.file("testing/statistics/rx_packets", "100").await?;
+
+    let res = probe.probe().await?;
+
+    assert!(res.interfaces[0].rx_packets_per_sec > 0.0);
+
+    Ok(())
+}
+
+const IF_INET6_STR: &str = "\
+fe8000000000000000fc00fffe000001 04 40 20 80     testing
+00000000000000000000000000000001 01 80 10 80       lo
+";
+
+const FIB_TRIE_STR: &str = "\
+Main:
+  +-- 0.0.0.0/0 3 0 5
+     |-- 0.0.0.0
+        /0 universe UNICAST
+     +-- 127.0.0.0/8 2 0 2
+        +-- 127.0.0.0/31 1 0 0
+           |-- 127.0.0.0
+              /8 host LOCAL
+           |-- 127.0.0.1
+              /32 host LOCAL
+        |-- 127.255.255.255
+           /32 link BROADCAST
+     +-- 192.0.2.0/24 2 0 2
+        +-- 192.0.2.0/30 2 0 2
+           |-- 192.0.2.0
+              /24 link UNICAST
+           |-- 192.0.2.2
+              /32 host LOCAL
+        |-- 192.0.2.255
+           /32 link BROADCAST
+";
+
+// the default route and 192.0.2.0/24,
+// as little endian hex
+const ROUTE_STR: &str = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+testing\t00000000\t010200C0\t0003\t0\t0\t0\t00000000\t0\t0\t0
+testing\t000200C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0
+";
+
+#[tokio::test]
+pub async fn test_probe_network_addresses() -> crate::Any {
+    let net = setup(OperState::Up, "1", false, false, false).await?;
+
+    net.file("testing/mtu", "1500").await?;
+    net.file("testing/duplex", "full").await?;
+    net.file("testing/carrier", "1").await?;
+    net.file("testing/carrier_changes", "3").await?;
+    net.dir("testing/device").await?;
+
+    let proc = TempDir::new().await?;
+
+    proc.file("if_inet6", IF_INET6_STR).await?;
+    proc.file("fib_trie", FIB_TRIE_STR).await?;
+    proc.file("route", ROUTE_STR).await?;
+
+    let nw = NetworkProbe::new(net.path(), proc.path()).probe().await?;
+
+    let info = &nw.interfaces[0].info;
+
+    assert_eq!(info.addresses, vec![
+        Address {
+            address: "192.0.2.2".parse()?,
+            prefix: 24,
+        },
+        Address {
+            address: "fe80::fc:ff:fe00:1".parse()?,
+            prefix: 64,
+        },
+    ]);
+
+    assert_eq!(info.kind, InterfaceType::Physical);
+    assert_eq!(info.duplex, Some(Duplex::Full));
+    assert_eq!(info.carrier_changes, Some(3));
+    assert_eq!(info.carrier, Some(true));
+    assert_eq!(info.mtu, Some(1500));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_network_kind() -> crate::Any {
+    let net = setup(OperState::Up, "1", false, false, false).await?;
+
+    let probe = NetworkProbe::new(net.path(), "/tmp/not/a/dir");
+
+    net.file("testing/type", "772").await?;
+
+    assert_eq!(probe.probe().await?.interfaces[0].info.kind, InterfaceType::Loopback);
+
+    net.file("testing/type", "1").await?;
+    net.file("testing/iflink", "7").await?;
+    net.file("testing/ifindex", "8").await?;
+
+    assert_eq!(probe.probe().await?.interfaces[0].info.kind, InterfaceType::Veth);
+
+    net.file("testing/uevent", "DEVTYPE=vlan\nINTERFACE=testing\n").await?;
+
+    assert_eq!(probe.probe().await?.interfaces[0].info.kind, InterfaceType::Vlan);
+
+    net.dir("testing/bridge").await?;
+    net.file("testing/uevent", "INTERFACE=testing\n").await?;
+
+    assert_eq!(probe.probe().await?.interfaces[0].info.kind, InterfaceType::Bridge);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_network_query() -> crate::Any {
+    let nw = network(Path::new("/sys/class/net"));
+
+    let query = |kind, state: &str| Query {
+        state: Some(state.to_string()),
+        kind,
+    };
+
+    assert_eq!(nw.clone().query(&query(None, "up")).interfaces.len(), 1);
+    assert_eq!(nw.clone().query(&query(None, "down")).interfaces.len(), 0);
+
+    let physical = query(Some(InterfaceType::Physical), "up");
+
+    assert_eq!(nw.clone().query(&physical).interfaces.len(), 0);
+
+    let rates = nw.rate_since(&nw, Duration::from_secs(1));
+
+    assert_eq!(rates.clone().query(&query(Some(InterfaceType::Virtual), "up")).interfaces.len(), 1);
+    assert_eq!(rates.query(&physical).interfaces.len(), 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_network_events_since() -> crate::Any {
+    let prev = network(Path::new("/sys/class/net"));
+
+    // nothing changed
+    assert!(prev.events_since(&prev, 10).is_empty());
+
+    let mut eth1 = prev.interfaces[0].clone();
+
+    eth1.name = "eth1".to_string();
+
+    let mut now = prev.clone();
+
+    now.interfaces[0].info.operstate = OperState::Down;
+    now.interfaces.push(eth1);
+
+    assert_eq!(now.events_since(&prev, 20), vec![
+        NetworkEvent {
+            interface: "testing".to_string(),
+            kind: EventKind::Link {
+                from: OperState::Up,
+                to: OperState::Down,
+            },
+            timestamp: 20,
+        },
+        NetworkEvent {
+            interface: "eth1".to_string(),
+            kind: EventKind::Added {
+                operstate: OperState::Up,
+            },
+            timestamp: 20,
+        },
+    ]);
+
+    assert_eq!(prev.events_since(&now, 30)[0], NetworkEvent {
+        interface: "eth1".to_string(),
+        kind: EventKind::Removed,
+        timestamp: 30,
+    });
+
+    // eth1 failing to be read
+    // isn't eth1 going away
+    let mut failed = now.clone();
+
+    failed.interfaces.retain(|x| x.name != "eth1");
+    failed.errors.insert("eth1".to_string(), "bad".to_string());
+
+    assert!(failed.events_since(&now, 40).is_empty());
+    assert!(now.events_since(&failed, 50).is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use monitor::probe::Probe;
+
+use crate::resources::pressure::{Error, Pressure, PressureProbe, Resource, Stall};
+
+use super::TempDir;
+
+const PRESSURE_STR: &str = "\
+some avg10=1.50 avg60=0.75 avg300=0.25 total=12345
+full avg10=0.50 avg60=0.25 avg300=0.00 total=678
+";
+
+const SOME: Stall = Stall {
+    avg10: 1.5,
+    avg60: 0.75,
+    avg300: 0.25,
+    total: 12345,
+};
+
+const FULL: Stall = Stall {
+    avg10: 0.5,
+    avg60: 0.25,
+    avg300: 0.0,
+    total: 678,
+};
+
+async fn setup(data: &str) -> anyhow::Result<TempDir> {
+    let pressure = TempDir::new().await?;
+
+    for x in ["cpu", "memory", "io"] {
+        pressure.file(x, data).await?;
+    }
+
+    Ok(pressure)
+}
+
+#[tokio::test]
+pub async fn test_probe_pressure_success() -> crate::Any {
+    let pressure = setup(PRESSURE_STR).await?;
+
+    // irq only reports full
+    let irq = PRESSURE_STR.lines().nth(1)
+        .unwrap_or_default();
+
+    pressure.file("irq", irq).await?;
+
+    let res = PressureProbe::new(pressure.path()).probe().await?;
+
+    let resource = Resource {
+        some: Some(SOME),
+        full: Some(FULL),
+    };
+
+    assert_eq!(res, Pressure {
+        memory: Some(resource),
+        cpu: Some(resource),
+        io: Some(resource),
+        irq: Some(Resource {
+            some: None,
+            full: Some(FULL),
+        }),
+        available: true,
+    });
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_pressure_no_irq() -> crate::Any {
+    let pressure = setup(PRESSURE_STR).await?;
+
+    let res = PressureProbe::new(pressure.path()).probe().await?;
+
+    assert!(res.available);
+    assert_eq!(res.irq, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_pressure_disabled() -> crate::Any {
+    let res = PressureProbe::new("/tmp/not/a/dir").probe().await?;
+
+    assert_eq!(res, Pressure::default());
+    assert!(!res.available);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_pressure_invalid_line() -> crate::Any {
+    let pressure = setup("partial avg10=0.00 avg60=0.00 avg300=0.00 total=0").await?;
+
+    let err = PressureProbe::new(pressure.path()).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::InvalidLine(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_pressure_missing_field() -> crate::Any {
+    let pressure = setup(&PRESSURE_STR.replace("total=678", "")).await?;
+
+    let err = PressureProbe::new(pressure.path()).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::MissingField(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_pressure_parse_float() -> crate::Any {
+    let pressure = setup(&PRESSURE_STR.replace("1.50", "abc")).await?;
+
+    let err = PressureProbe::new(pressure.path()).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::ParseFloat(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_pressure_parse_int() -> crate::Any {
+    let pressure = setup(&PRESSURE_STR.replace("12345", "abc")).await?;
+
+    let err = PressureProbe::new(pressure.path()).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::ParseInt(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_pressure_io() -> crate::Any {
+    // a directory where a file
+    // is expected can't be read
+    let pressure = setup(PRESSURE_STR).await?;
+
+    pressure.dir("irq").await?;
+
+    let err = PressureProbe::new(pressure.path()).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::Io(_)));
+
+    Ok(())
+}
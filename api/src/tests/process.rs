@@ -0,0 +1,448 @@
+use std::time::Duration;
+
+use monitor::probe::Probe;
+
+use crate::resources::{
+    cpu::{CoresUsageProbe, UsageError, UsageProbe},
+    memory::MemoryProbe,
+    process::{
+        Error, Order, Process, Processes, ProcessesProbe, Query, SortBy, State, Top,
+        SnapshotProbe, TopBy, TopError, TopProbe, TopProcess, TopQuery, Tree,
+    },
+};
+
+use super::TempDir;
+
+const PASSWD_STR: &str = "\
+root:x:0:0:root:/root:/bin/bash
+daemon:x:1:1:daemon:/usr/sbin:/usr/sbin/nologin
+";
+
+fn stat(pid: u32, name: &str, ppid: u32, utime: u64, threads: u64) -> String {
+    format!(
+        "{pid} ({name}) S {ppid} 1 1 0 -1 4194560 100 0 0 0 {utime} 50 0 0 20 0 {threads} 0 1234 1000 200 \
+        18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 0 0 0 0 0 0 0 0 0 0 0 0 0 0"
+    )
+}
+
+fn status(name: &str, uid: u32, rss: Option<u64>) -> String {
+    let rss = rss.map(|x| format!("VmRSS:\t{x} kB\n"))
+        .unwrap_or_default();
+
+    format!("Name:\t{name}\nState:\tS (sleeping)\nUid:\t{uid}\t{uid}\t{uid}\t{uid}\n{rss}Threads:\t1\n")
+}
+
+async fn setup() -> anyhow::Result<TempDir> {
+    let proc = TempDir::new().await?;
+
+    proc.file("1/stat", stat(1, "init", 0, 150, 1)).await?;
+    proc.file("1/status", status("init", 0, Some(4))).await?;
+    proc.file("1/cmdline", "/sbin/init\0splash\0").await?;
+
+    proc.file("42/stat", stat(42, "my (odd) name", 1, 50, 8)).await?;
+    proc.file("42/status", status("my (odd) name", 1, Some(16))).await?;
+    proc.file("42/cmdline", "server\0").await?;
+
+    // kernel threads have no memory
+    // and no command line
+    proc.file("2/stat", stat(2, "kthreadd", 0, 0, 1)).await?;
+    proc.file("2/status", status("kthreadd", 0, None)).await?;
+    proc.file("2/cmdline", "").await?;
+
+    proc.file("self/stat", "").await?;
+    proc.file("uptime", "").await?;
+
+    proc.file("passwd", PASSWD_STR).await?;
+
+    Ok(proc)
+}
+
+const MEMINFO_STR: &str = "\
+MemTotal:       64 kB
+MemFree:        32 kB
+MemAvailable:   48 kB
+Buffers:        1 kB
+Cached:         2 kB
+SwapCached:     0 kB
+Active:         3 kB
+Inactive:       4 kB
+Active(anon):   1 kB
+Inactive(anon): 1 kB
+Active(file):   2 kB
+Inactive(file): 3 kB
+Unevictable:    0 kB
+Mlocked:        0 kB
+SwapTotal:      0 kB
+SwapFree:       0 kB
+Dirty:          0 kB
+";
+
+// 36 jiffies in total, then 136
+const STAT_STR: &str = "\
+cpu  1 2 3 4 5 6 7 8 9 10
+cpu0 1 2 3 4 5 6 7 8 9 10
+cpu1 1 2 3 4 5 6 7 8 9 10
+";
+
+const STAT_NEXT_STR: &str = "\
+cpu  21 2 23 44 15 6 7 18 19 10
+cpu0 11 2 13 24 5 6 7 8 9 10
+cpu1 11 2 13 24 5 6 7 8 9 10
+";
+
+fn probe(proc: &TempDir) -> ProcessesProbe {
+    ProcessesProbe::new(
+        proc.path(),
+        proc.path().join("passwd"),
+    )
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_success() -> crate::Any {
+    let proc = setup().await?;
+
+    let res = probe(&proc).probe().await?;
+
+    let pids = res.processes.iter()
+        .map(|x| x.pid)
+        .collect::<Vec<u32>>();
+
+    assert_eq!(pids, vec![1, 2, 42]);
+
+    let ticks = crate::resources::process::ticks();
+
+    assert_eq!(res.processes[2], Process {
+        user: Some("daemon".to_string()),
+        cmdline: vec!["server".to_string()],
+        cpu_time: Duration::from_secs_f64(100.0 / ticks as f64),
+        name: "my (odd) name".to_string(),
+        state: State::Sleeping,
+        start_time: 1234,
+        threads: 8,
+        utime: 50,
+        stime: 50,
+        rss: 16 * 1024,
+        ppid: 1,
+        pid: 42,
+        uid: 1,
+    });
+
+    assert_eq!(res.processes[1].rss, 0);
+    assert!(res.processes[1].cmdline.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_query() -> crate::Any {
+    let proc = setup().await?;
+
+    let res = probe(&proc).probe().await?;
+
+    let names = |x: Processes| x.processes.into_iter()
+        .map(|x| x.name)
+        .collect::<Vec<String>>();
+
+    let query = Query {
+        sort: Some(SortBy::Rss),
+        n: Some(2),
+        ..Default::default()
+    };
+
+    assert_eq!(names(res.clone().query(&query)), vec!["my (odd) name", "init"]);
+
+    let query = Query {
+        user: Some("root".to_string()),
+        sort: Some(SortBy::Name),
+        order: Some(Order::Desc),
+        ..Default::default()
+    };
+
+    assert_eq!(names(res.clone().query(&query)), vec!["kthreadd", "init"]);
+
+    let query = Query {
+        name: Some("odd".to_string()),
+        ..Default::default()
+    };
+
+    assert_eq!(names(res.clone().query(&query)), vec!["my (odd) name"]);
+
+    let query = Query {
+        user: Some("0".to_string()),
+        sort: Some(SortBy::CpuTime),
+        ..Default::default()
+    };
+
+    assert_eq!(names(res.query(&query)), vec!["init", "kthreadd"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_no_passwd() -> crate::Any {
+    let proc = setup().await?;
+
+    let res = ProcessesProbe::new(proc.path(), "/tmp/not/a/file")
+        .probe().await?;
+
+    assert!(res.processes.iter().all(|x| x.user.is_none()));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_invalid_stat() -> crate::Any {
+    let proc = setup().await?;
+
+    proc.file("42/stat", "42 (server) S 1 1").await?;
+
+    let err = probe(&proc).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::InvalidStat(42)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_missing_field() -> crate::Any {
+    let proc = setup().await?;
+
+    proc.file("42/status", "Name:\tserver\n").await?;
+
+    let err = probe(&proc).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::MissingField { pid: 42, .. }));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_parse_int() -> crate::Any {
+    let proc = setup().await?;
+
+    proc.file("42/stat", stat(42, "server", 1, 50, 8).replace(" 1234 ", " abc ")).await?;
+
+    let err = probe(&proc).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::ParseInt(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_vanished() -> crate::Any {
+    let proc = setup().await?;
+
+    // a process that exited between listing
+    // /proc and reading its files is skipped
+    proc.dir("77").await?;
+
+    let res = probe(&proc).probe().await?;
+
+    assert_eq!(res.processes.len(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_io() -> crate::Any {
+    let err = ProcessesProbe::new("/tmp/not/a/dir", "/tmp/not/a/file")
+        .probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::Io(_)));
+
+    Ok(())
+}
+
+async fn top(proc: &TempDir) -> anyhow::Result<TopProbe> {
+    let stat = proc.file("stat", STAT_STR).await?;
+    let meminfo = proc.file("meminfo", MEMINFO_STR).await?;
+
+    Ok(TopProbe::new(SnapshotProbe::new(
+        probe(proc),
+        CoresUsageProbe::new(&stat),
+        MemoryProbe::new(meminfo),
+        UsageProbe::new(stat),
+    )))
+}
+
+fn percents(res: &Top) -> Vec<(u32, f64, f64)> {
+    res.processes.iter()
+        .map(|x| (x.process.pid, x.cpu_percent, x.mem_percent))
+        .collect()
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_top_success() -> crate::Any {
+    let proc = setup().await?;
+    let top = top(&proc).await?;
+
+    // nothing ran between the first two samples
+    let res = top.probe().await?;
+
+    assert_eq!(percents(&res), vec![
+        (1, 0.0, 6.25),
+        (2, 0.0, 0.0),
+        (42, 0.0, 25.0),
+    ]);
+
+    proc.file("stat", STAT_NEXT_STR).await?;
+    proc.file("1/stat", stat(1, "init", 0, 175, 1)).await?;
+    proc.file("42/stat", stat(42, "my (odd) name", 1, 60, 8)).await?;
+
+    // 25 and 10 of 100 jiffies on 2 cpus
+    let res = top.probe().await?;
+
+    assert_eq!(percents(&res), vec![
+        (1, 50.0, 6.25),
+        (42, 20.0, 25.0),
+        (2, 0.0, 0.0),
+    ]);
+
+    assert_eq!(res.processes[0].process.user.as_deref(), Some("root"));
+
+    let query = TopQuery {
+        by: Some(TopBy::Memory),
+        n: Some(2),
+    };
+
+    let pids = res.query(&query).processes.iter()
+        .map(|x| x.process.pid)
+        .collect::<Vec<u32>>();
+
+    assert_eq!(pids, vec![42, 1]);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_top_reused_pid() -> crate::Any {
+    let proc = setup().await?;
+    let top = top(&proc).await?;
+
+    top.probe().await?;
+
+    // 42 exited and its pid went to a new process,
+    // so all of its 60 jiffies are since the last probe
+    proc.file("stat", STAT_NEXT_STR).await?;
+    proc.file("42/stat", stat(42, "new", 1, 10, 1).replace(" 1234 ", " 5678 ")).await?;
+
+    let res = top.probe().await?;
+
+    assert_eq!(res.processes[0].process.name, "new");
+    assert_eq!(res.processes[0].cpu_percent, 120.0);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_top_usage() -> crate::Any {
+    let proc = setup().await?;
+    let top = top(&proc).await?;
+
+    proc.file("stat", "cpu  1 2 3").await?;
+
+    let err = top.probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, TopError::Usage(UsageError::InvalidCpuLine)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_processes_tree() -> crate::Any {
+    let proc = setup().await?;
+
+    // a child of 42 and an orphan whose
+    // parent is outside the namespace
+    proc.file("43/stat", stat(43, "worker", 42, 0, 1)).await?;
+    proc.file("43/status", status("worker", 1, Some(8))).await?;
+    proc.file("43/cmdline", "worker\0").await?;
+
+    proc.file("50/stat", stat(50, "orphan", 9, 0, 1)).await?;
+    proc.file("50/status", status("orphan", 1, None)).await?;
+    proc.file("50/cmdline", "").await?;
+
+    let top = top(&proc).await?;
+
+    top.probe().await?;
+
+    proc.file("stat", STAT_NEXT_STR).await?;
+    proc.file("1/stat", stat(1, "init", 0, 175, 1)).await?;
+    proc.file("42/stat", stat(42, "my (odd) name", 1, 60, 8)).await?;
+    proc.file("43/stat", stat(43, "worker", 42, 5, 1)).await?;
+
+    let tree = Tree::from(top.probe().await?);
+
+    let roots = tree.roots.iter()
+        .map(|x| x.process.process.pid)
+        .collect::<Vec<u32>>();
+
+    assert_eq!(roots, vec![1, 2, 50]);
+
+    let init = &tree.roots[0];
+
+    assert_eq!(init.children.len(), 1);
+    assert_eq!(init.children[0].children[0].process.process.pid, 43);
+
+    // 25 + 10 + 5 of 100 jiffies on 2 cpus
+    assert_eq!(init.subtree.cpu_percent, 80.0);
+    assert_eq!(init.subtree.mem_percent, 43.75);
+    assert_eq!(init.subtree.rss, 28 * 1024);
+    assert_eq!(init.subtree.processes, 3);
+
+    assert_eq!(init.children[0].subtree.rss, 24 * 1024);
+    assert_eq!(tree.roots[2].subtree.processes, 1);
+
+    Ok(())
+}
+
+fn top_process(pid: u32, ppid: u32) -> TopProcess {
+    TopProcess {
+        process: Process {
+            pid,
+            ppid,
+            rss: 1024,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+pub async fn test_process_tree_cycle() -> crate::Any {
+    // 10 and 11 are each other's parent, with
+    // 12 under 11 and 13 under 12
+    let top = Top {
+        processes: vec![
+            top_process(13, 12),
+            top_process(12, 11),
+            top_process(11, 10),
+            top_process(10, 11),
+            top_process(1, 0),
+        ],
+    };
+
+    let tree = Tree::from(top);
+
+    let roots = tree.roots.iter()
+        .map(|x| x.process.process.pid)
+        .collect::<Vec<u32>>();
+
+    // the cycle is broken at its lowest pid
+    assert_eq!(roots, vec![1, 10]);
+
+    let cycle = &tree.roots[1];
+
+    assert_eq!(cycle.subtree.processes, 4);
+    assert_eq!(cycle.subtree.rss, 4 * 1024);
+    assert_eq!(cycle.children[0].process.process.pid, 11);
+    assert_eq!(cycle.children[0].children[0].children[0].process.process.pid, 13);
+
+    Ok(())
+}
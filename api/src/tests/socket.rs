@@ -0,0 +1,147 @@
+use monitor::probe::Probe;
+
+use crate::resources::socket::{
+    Error, Listener, Protocol, Query, Socket, SocketsProbe, TcpState,
+};
+
+use super::TempDir;
+
+const TCP_STR: &str = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0035 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 100 1 0 100 0 0 10 0
+   1: 0100007F:1F90 0100007F:C350 01 00000010:00000020 00:00000000 00000000  1000        0 101 1 0 20 4 30 10 -1
+   2: 0100007F:1F90 0100007F:C351 08 00000000:00000000 00:00000000 00000000  1000        0 102 1 0 20 4 30 10 -1
+   3: 0100007F:1F90 0100007F:C352 08 00000000:00000000 00:00000000 00000000  1000        0 103 1 0 20 4 30 10 -1
+";
+
+const TCP6_STR: &str = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000000000000:0016 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 200 1 0 100 0 0 10 0
+";
+
+const UDP_STR: &str = "\
+   sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode ref pointer drops
+  10: 00000000:0044 00000000:0000 07 00000000:00000000 00:00000000 00000000     0        0 300 2 0 0
+  11: 0200000A:D431 08080808:0035 01 00000000:00000000 00:00000000 00000000  1000        0 301 2 0 0
+";
+
+async fn setup() -> anyhow::Result<TempDir> {
+    let net = TempDir::new().await?;
+
+    net.file("tcp", TCP_STR).await?;
+    net.file("tcp6", TCP6_STR).await?;
+    net.file("udp", UDP_STR).await?;
+
+    // no udp6, as if ipv6 udp wasn't there
+
+    Ok(net)
+}
+
+#[tokio::test]
+pub async fn test_probe_sockets_success() -> crate::Any {
+    let net = setup().await?;
+
+    let res = SocketsProbe::new(net.path()).probe().await?;
+
+    assert_eq!(res.tcp, 5);
+    assert_eq!(res.udp, 2);
+
+    assert_eq!(res.states.get(&TcpState::Listen), Some(&2));
+    assert_eq!(res.states.get(&TcpState::Established), Some(&1));
+    assert_eq!(res.states.get(&TcpState::CloseWait), Some(&2));
+    assert_eq!(res.states.get(&TcpState::TimeWait), None);
+
+    assert_eq!(res.listening, vec![
+        Listener {
+            protocol: Protocol::Tcp,
+            address: "127.0.0.1:53".parse()?,
+        },
+        Listener {
+            protocol: Protocol::Tcp6,
+            address: "[::]:22".parse()?,
+        },
+        Listener {
+            protocol: Protocol::Udp,
+            address: "0.0.0.0:68".parse()?,
+        },
+    ]);
+
+    let sockets = res.sockets.unwrap();
+
+    assert_eq!(sockets.len(), 7);
+
+    assert_eq!(sockets[1], Socket {
+        protocol: Protocol::Tcp,
+        local: "127.0.0.1:8080".parse()?,
+        remote: "127.0.0.1:50000".parse()?,
+        state: TcpState::Established,
+        tx_queue: 16,
+        rx_queue: 32,
+        inode: 101,
+        uid: 1000,
+    });
+
+    assert_eq!(sockets[6].remote, "8.8.8.8:53".parse()?);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_sockets_query() -> crate::Any {
+    let net = setup().await?;
+
+    let res = SocketsProbe::new(net.path()).probe().await?;
+
+    let table = Query {
+        table: true,
+    };
+
+    assert!(res.clone().query(&table).sockets.is_some());
+    assert!(res.query(&Query::default()).sockets.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_sockets_invalid_line() -> crate::Any {
+    let net = setup().await?;
+
+    net.file("udp", format!("{UDP_STR}  12: 00000000:0044\n")).await?;
+
+    let err = SocketsProbe::new(net.path()).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::InvalidLine(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_sockets_parse_int() -> crate::Any {
+    let net = setup().await?;
+
+    net.file("tcp6", TCP6_STR.replace(" 200 ", " abc ")).await?;
+
+    let err = SocketsProbe::new(net.path()).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::ParseInt(_)));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_probe_sockets_io() -> crate::Any {
+    let net = setup().await?;
+
+    // a table that can't be read, unlike
+    // one that just isn't there
+    net.dir("udp6").await?;
+
+    let err = SocketsProbe::new(net.path()).probe().await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::Io(_)));
+
+    Ok(())
+}
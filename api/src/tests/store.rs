@@ -0,0 +1,198 @@
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::{fs, io::AsyncWriteExt};
+
+use crate::{
+    history::Sample,
+    store::{Store, Tier},
+};
+
+use super::TempDir;
+
+fn tiers() -> [Tier; 2] {
+    [
+        Tier::new(Duration::from_secs(1), Duration::from_secs(80)),
+        Tier::new(Duration::from_secs(10), Duration::from_secs(800)),
+    ]
+}
+
+fn values(samples: Vec<Sample<u64>>) -> Vec<u64> {
+    samples.into_iter()
+        .map(|x| x.value)
+        .collect()
+}
+
+#[tokio::test]
+pub async fn test_store_downsampling() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let store = Store::<u64>::open(tmp.path(), &tiers())?;
+
+    // every 500ms, so half of the samples
+    // share a window even in the first tier
+    for x in 0..60 {
+        store.append(&Sample::new(x * 500, x))?;
+    }
+
+    // the mean of each window, rounded
+    // since the samples are integers
+    assert_eq!(values(store.read(0, 0, 4000)?), [1, 3, 5, 7, 9]);
+    assert_eq!(values(store.read(1, 0, u64::MAX)?), [10, 30, 50]);
+    assert_eq!(values(store.read(1, 10_000, 19_999)?), [30]);
+    assert!(store.read(2, 0, u64::MAX)?.is_empty());
+
+    let res = store.read(1, 0, u64::MAX)?;
+
+    assert_eq!(
+        res.iter().map(|x| x.timestamp).collect::<Vec<_>>(),
+        [0, 10_000, 20_000]
+    );
+    assert_eq!(
+        res.into_iter().map(|x| x.max).collect::<Vec<_>>(),
+        [Some(19), Some(39), Some(59)]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_store_downsampling_devices() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let store = Store::<serde_json::Value>::open(tmp.path(), &tiers())?;
+
+    // disks coming and going within a window,
+    // so their positions don't line up
+    let samples = [
+        json!([{ "name": "sda", "reads": 10 }, { "name": "sdb", "reads": 100 }]),
+        json!([{ "name": "sdb", "reads": 200 }]),
+        json!([{ "name": "sdc", "reads": 1 }, { "name": "sda", "reads": 30 }]),
+    ];
+
+    for (x, value) in samples.into_iter().enumerate() {
+        store.append(&Sample::new(x as u64 * 1000, value))?;
+    }
+
+    let res = store.read(1, 0, u64::MAX)?;
+
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].value, json!([
+        { "name": "sda", "reads": 20 },
+        { "name": "sdb", "reads": 150 },
+        { "name": "sdc", "reads": 1 },
+    ]));
+    assert_eq!(res[0].max, Some(json!([
+        { "name": "sda", "reads": 30 },
+        { "name": "sdb", "reads": 200 },
+        { "name": "sdc", "reads": 1 },
+    ])));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_store_retention() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let store = Store::<u64>::open(tmp.path(), &tiers())?;
+
+    for x in 0..200 {
+        store.append(&Sample::new(x * 1000, x))?;
+    }
+
+    // the first tier has 10s segments, so up to a
+    // segment past the retention can still be there
+    let res = values(store.read(0, 0, u64::MAX)?);
+
+    assert_eq!(res.last(), Some(&199));
+    assert!(res.len() >= 80 && res.len() <= 90);
+
+    assert_eq!(values(store.read(1, 0, u64::MAX)?).len(), 20);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_store_reopen() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let store = Store::<u64>::open(tmp.path(), &tiers())?;
+
+    for x in 0..5 {
+        store.append(&Sample::new(x * 1000, x))?;
+    }
+
+    drop(store);
+
+    let store = Store::<u64>::open(tmp.path(), &tiers())?;
+
+    // the windows carry over, so a sample in
+    // the last one isn't written twice
+    store.append(&Sample::new(4500, 100))?;
+    store.append(&Sample::new(5000, 5))?;
+
+    assert_eq!(values(store.read(0, 0, u64::MAX)?), [0, 1, 2, 3, 4, 5]);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_store_unclean_shutdown() -> crate::Any {
+    let tmp = TempDir::new().await?;
+
+    let store = Store::<u64>::open(tmp.path(), &tiers())?;
+
+    for x in 0..5 {
+        store.append(&Sample::new(x * 1000, x))?;
+    }
+
+    // killed without a chance to flush,
+    // halfway through writing a sample
+    std::mem::forget(store);
+
+    let segment = tmp.path().join("1s").join("0.jsonl");
+
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(&segment)
+        .await?;
+
+    file.write_all(b"{\"timestamp\":5000,\"va").await?;
+    file.flush().await?;
+
+    let store = Store::<u64>::open(tmp.path(), &tiers())?;
+
+    // only the window being aggregated is lost
+    assert_eq!(values(store.read(0, 0, u64::MAX)?), [0, 1, 2, 3]);
+
+    for x in 4..7 {
+        store.append(&Sample::new(x * 1000, x))?;
+    }
+
+    assert_eq!(values(store.read(0, 0, u64::MAX)?), [0, 1, 2, 3, 4, 5, 6]);
+
+    store.flush()?;
+
+    std::mem::forget(store);
+
+    // the torn sample is gone from the file
+    // rather than just skipped, and the last
+    // window is there once flushed
+    let res = fs::read_to_string(&segment).await?;
+
+    assert_eq!(res.lines().count(), 7);
+    assert!(res.lines().all(|x| x.starts_with('{') && x.ends_with('}')));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_store_invalid_dir() -> crate::Any {
+    let tmp = TempDir::new().await?;
+    let file = tmp.file("store", "").await?;
+
+    assert!(Store::<u64>::open(file, &tiers()).is_err());
+
+    Ok(())
+}
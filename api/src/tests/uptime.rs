@@ -2,7 +2,10 @@ use std::time::Duration;
 
 use monitor::probe::Probe;
 
-use crate::resources::uptime::{Error, Uptime};
+use crate::resources::{
+    uptime::{Error, Uptime, UptimeProbe},
+    Source,
+};
 
 const UPTIME_STR: &str = "10.0 20.0";
 
@@ -12,17 +15,14 @@ const UPTIME: Uptime = Uptime {
 };
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_uptime_parse_float() -> crate::Any {
     let data = UPTIME_STR.replace("20.0", "abc");
 
-    super::point_env_file(
-        "UPTIME",
-        "/tmp/uptime",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
+
+    let uptime = tmp.file("uptime", &data).await?;
 
-    let err = Uptime::probe().await
+    let err = UptimeProbe::new(uptime).probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::ParseFloat(_)));
@@ -31,17 +31,14 @@ pub async fn test_probe_uptime_parse_float() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_uptime_invalid() -> crate::Any {
     let data = format!("{} 30.0", UPTIME_STR);
 
-    super::point_env_file(
-        "UPTIME",
-        "/tmp/uptime",
-        &data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Uptime::probe().await
+    let uptime = tmp.file("uptime", &data).await?;
+
+    let err = UptimeProbe::new(uptime).probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::InvalidUptime));
@@ -50,17 +47,14 @@ pub async fn test_probe_uptime_invalid() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_uptime_success() -> crate::Any {
     let data = UPTIME_STR;
 
-    super::point_env_file(
-        "UPTIME",
-        "/tmp/uptime",
-        data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
+
+    let uptime = tmp.file("uptime", data).await?;
 
-    let uptime = Uptime::probe().await?;
+    let uptime = UptimeProbe::new(uptime).probe().await?;
 
     assert_eq!(uptime, UPTIME);
 
@@ -68,17 +62,14 @@ pub async fn test_probe_uptime_success() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_uptime_empty() -> crate::Any {
     let data = "";
 
-    super::point_env_file(
-        "UPTIME",
-        "/tmp/uptime",
-        data,
-    ).await?;
+    let tmp = super::TempDir::new().await?;
 
-    let err = Uptime::probe().await
+    let uptime = tmp.file("uptime", data).await?;
+
+    let err = UptimeProbe::new(uptime).probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::Empty));
@@ -87,14 +78,33 @@ pub async fn test_probe_uptime_empty() -> crate::Any {
 }
 
 #[tokio::test]
-#[serial_test::serial]
 pub async fn test_probe_uptime_io() -> crate::Any {
-    std::env::set_var("UPTIME", "/tmp/not/a/file");
-
-    let err = Uptime::probe().await
+    let err = UptimeProbe::new("/tmp/not/a/file").probe().await
         .unwrap_err();
 
     assert!(matches!(err, Error::Io(_)));
 
     Ok(())
 }
+
+#[tokio::test]
+pub async fn test_probe_uptime_sources() -> crate::Any {
+    let host = super::TempDir::new().await?;
+    let guest = super::TempDir::new().await?;
+
+    host.file("uptime", UPTIME_STR).await?;
+    guest.file("uptime", "30.0 40.0").await?;
+
+    let host = UptimeProbe::from(
+        &Source::new(host.path(), "/sys")
+    );
+
+    let guest = UptimeProbe::from(
+        &Source::new(guest.path(), "/sys")
+    );
+
+    assert_eq!(host.probe().await?, UPTIME);
+    assert_eq!(guest.probe().await?.uptime, Duration::new(30, 0));
+
+    Ok(())
+}
@@ -1,4 +1,14 @@
-use std::future::Future;
+use std::{future::Future, time::Duration};
+
+use futures_util::Stream;
+use tokio::{sync::watch, task::AbortHandle};
+use tokio_stream::wrappers::WatchStream;
+
+#[derive(
+    PartialEq, Eq,
+    Debug,
+    Clone,
+)]
 
 pub enum Health {
     Unhealthy {
@@ -8,38 +18,165 @@ pub enum Health {
     Healthy,
 }
 
+impl Health {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, Self::Healthy)
+    }
+}
+
 // Something that can be
 // healthy / unhealthy
+//
+// Health is checked every CHECK_DELAY
+// while waiting on it, sleeping in
+// between so the runtime is free to
+// do other work. Dropping any of the
+// futures or streams stops checking.
 pub trait Healthcheck {
-    fn on_unhealthy(&self, func: impl Fn()) -> impl Future {
-        async move {
-            let Health::Healthy = self.health() else {
-                func(); return;
-            };
+    fn health(&self) -> impl Future<Output = Health> + Send;
 
-            while let Health::Healthy = self.health() {
-                std::hint::spin_loop();
+    // resolves once unhealthy, right
+    // away if it already is
+    fn on_unhealthy(&self, func: impl FnOnce()) -> impl Future<Output = ()> {
+        async move {
+            while self.health().await.is_healthy() {
+                tokio::time::sleep(
+                    Duration::from_millis(
+                        Self::CHECK_DELAY
+                    )
+                ).await;
             }
 
             func();
         }
     }
 
-    fn on_healthy(&self, func: impl Fn()) -> impl Future {
+    // resolves once healthy, right
+    // away if it already is
+    fn on_healthy(&self, func: impl FnOnce()) -> impl Future<Output = ()> {
         async move {
-            let Health::Unhealthy { .. } = self.health() else {
-                func(); return;
-            };
-
-            while let Health::Unhealthy {
-                ..
-            } = self.health() {
-                std::hint::spin_loop();
+            while !self.health().await.is_healthy() {
+                tokio::time::sleep(
+                    Duration::from_millis(
+                        Self::CHECK_DELAY
+                    )
+                ).await;
             }
 
             func();
         }
     }
 
-    fn health(&self) -> Health;
+    // the current health, then every
+    // change between healthy and unhealthy
+    fn transitions(&self) -> impl Stream<Item = Health> + '_ {
+        futures_util::stream::unfold(None, move |last: Option<bool>| {
+            async move {
+                loop {
+                    if last.is_some() {
+                        tokio::time::sleep(
+                            Duration::from_millis(
+                                Self::CHECK_DELAY
+                            )
+                        ).await;
+                    }
+
+                    let health = self.health().await;
+
+                    if last != Some(health.is_healthy()) {
+                        let healthy = health.is_healthy();
+
+                        return Some((health, Some(healthy)));
+                    }
+                }
+            }
+        })
+    }
+
+    // checks health in the background, for
+    // sharing between many subscribers
+    fn watch(self) -> impl Future<Output = HealthWatch> + Send
+    where
+        Self: Sized + Send + Sync + 'static,
+    {
+        async move {
+            let (tx, rx) = watch::channel(self.health().await);
+
+            let task = tokio::spawn(async move {
+                loop {
+                    tokio::select! {
+                        _ = tx.closed() => return,
+                        _ = tokio::time::sleep(
+                            Duration::from_millis(
+                                Self::CHECK_DELAY
+                            )
+                        ) => {}
+                    }
+
+                    let health = self.health().await;
+
+                    // the reason is kept up to date, but
+                    // only transitions wake subscribers
+                    tx.send_if_modified(move |x| {
+                        let changed = x.is_healthy() != health.is_healthy();
+
+                        *x = health;
+
+                        changed
+                    });
+                }
+            });
+
+            HealthWatch {
+                task: task.abort_handle(),
+                receiver: rx,
+            }
+        }
+    }
+
+    const CHECK_DELAY: u64 = 1000; // MS
+}
+
+/// The health of a [`Healthcheck`], checked
+/// by a background task until cancelled or
+/// dropped
+pub struct HealthWatch {
+    receiver: watch::Receiver<Health>,
+    task: AbortHandle,
+}
+
+impl HealthWatch {
+    /// The latest health, without waiting
+    pub fn health(&self) -> Health {
+        self.receiver.borrow().clone()
+    }
+
+    /// A receiver that's notified on every
+    /// change between healthy and unhealthy
+    pub fn subscribe(&self) -> watch::Receiver<Health> {
+        self.receiver.clone()
+    }
+
+    /// The current health, then every change
+    /// between healthy and unhealthy. Ends
+    /// once the watch is cancelled.
+    pub fn transitions(&self) -> impl Stream<Item = Health> {
+        WatchStream::new(self.receiver.clone())
+    }
+
+    /// Stops checking, ending every
+    /// subscriber's transitions
+    pub fn cancel(&self) {
+        self.task.abort();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for HealthWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
 }
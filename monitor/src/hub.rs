@@ -0,0 +1,134 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures_util::Stream;
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+
+use crate::probe::Probe;
+
+// How many samples a slow subscriber
+// may fall behind before it starts
+// skipping samples
+const CAPACITY: usize = 16;
+
+/// Shares a single sampling loop of a
+/// [`Probe`] between every subscriber.
+///
+/// The loop is spawned when the first
+/// subscriber shows up and stops once
+/// the last one is dropped, so idle
+/// resources are never probed.
+pub struct Hub<P: Probe> {
+    sender: Arc<Mutex<Option<broadcast::Sender<P::Output>>>>,
+    probe: Arc<P>,
+}
+
+impl<P: Probe> Clone for Hub<P> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            probe: self.probe.clone(),
+        }
+    }
+}
+
+impl<P: Probe + Default> Default for Hub<P> {
+    fn default() -> Self {
+        Self::new(P::default())
+    }
+}
+
+impl<P: Probe> Hub<P> {
+    pub fn new(probe: P) -> Self {
+        Self {
+            sender: Arc::new(Mutex::new(None)),
+            probe: Arc::new(probe),
+        }
+    }
+
+    /// The probe backing this hub, for
+    /// one-off probing outside the loop
+    pub fn probe(&self) -> &P {
+        &self.probe
+    }
+}
+
+impl<P> Hub<P>
+where
+    P: Probe + Send + Sync + 'static,
+    P::Output: Clone + Send + 'static,
+{
+    /// Subscribes to the shared sampling loop,
+    /// starting it if nobody was listening
+    pub fn subscribe(&self) -> broadcast::Receiver<P::Output> {
+        let mut sender = self.sender.lock()
+            .unwrap_or_else(|x| x.into_inner());
+
+        if let Some(tx) = sender.as_ref() {
+            return tx.subscribe();
+        }
+
+        let (tx, rx) = broadcast::channel(CAPACITY);
+
+        *sender = Some(tx.clone());
+
+        tokio::spawn(Self::sample(
+            self.sender.clone(),
+            self.probe.clone(),
+            tx,
+        ));
+
+        rx
+    }
+
+    /// Same as [`Probe::stream`], but backed by
+    /// the shared sampling loop. Samples missed
+    /// by a lagging subscriber are skipped.
+    pub fn stream(&self) -> impl Stream<Item = P::Output> {
+        BroadcastStream::new(self.subscribe())
+            .filter_map(|x| x.ok())
+    }
+
+    /// The amount of subscribers currently
+    /// listening to the sampling loop
+    pub fn subscribers(&self) -> usize {
+        self.sender.lock()
+            .unwrap_or_else(|x| x.into_inner())
+            .as_ref()
+            .map(|x| x.receiver_count())
+            .unwrap_or_default()
+    }
+
+    async fn sample(
+        slot: Arc<Mutex<Option<broadcast::Sender<P::Output>>>>,
+        probe: Arc<P>,
+        tx: broadcast::Sender<P::Output>,
+    ) {
+        loop {
+            tokio::time::sleep(
+                Duration::from_millis(
+                    P::PROBE_DELAY
+                )
+            ).await;
+
+            // checked under the lock so nobody can
+            // subscribe to a loop that's stopping
+            {
+                let mut slot = slot.lock()
+                    .unwrap_or_else(|x| x.into_inner());
+
+                if tx.receiver_count() == 0 {
+                    *slot = None;
+                    return;
+                }
+            }
+
+            if let Ok(x) = probe.probe().await {
+                let _ = tx.send(x);
+            }
+        }
+    }
+}
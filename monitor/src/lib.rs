@@ -1,2 +1,5 @@
 pub mod health_check;
 pub mod probe;
+pub mod hub;
+pub mod rate;
+pub mod tests;
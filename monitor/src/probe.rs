@@ -6,13 +6,18 @@ use tokio_stream::StreamExt;
 // Something that can be probed--
 // Useful for getting information
 // from a resource
+//
+// Probes are values, so they carry
+// their own configuration (e.g. the
+// files they read) and several can
+// run side by side in one process
 pub trait Probe {
-    fn probe() -> impl Future<Output = Result<Self::Output, Self::Error>>;
+    fn probe(&self) -> impl Future<Output = Result<Self::Output, Self::Error>> + Send;
 
     // probes the resource repeatedly, filtering out
     // any failed probe actions
-    fn stream() -> impl Stream<Item = Self::Output> {
-        futures_util::stream::repeat_with(|| {
+    fn stream(&self) -> impl Stream<Item = Self::Output> + '_ {
+        futures_util::stream::repeat_with(move || {
             async move {
                 tokio::time::sleep(
                     Duration::from_millis(
@@ -20,7 +25,7 @@ pub trait Probe {
                     )
                 ).await;
 
-                Self::probe().await
+                self.probe().await
             }
         })
         .then(|x| x)
@@ -0,0 +1,147 @@
+use std::{
+    fmt,
+    sync::Mutex,
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::probe::Probe;
+
+// A probe of counters that only ever
+// go up, so the difference between
+// two samples of it is a rate
+pub trait Counters: Probe {
+    // the rate between two samples, or None
+    // if there is none (e.g. the counters
+    // were reset in between)
+    fn rate_since(
+        now: &Self::Output,
+        prev: &Self::Output,
+        elapsed: Duration,
+    ) -> Option<Self::Rate>;
+
+    type Rate;
+}
+
+#[derive(
+    PartialEq, Eq,
+    Debug,
+    Clone,
+    Copy,
+)]
+
+/// The counters were reset, or didn't
+/// move, between every pair of samples
+/// taken
+pub struct CounterReset;
+
+impl fmt::Display for CounterReset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "counters never went up between samples")
+    }
+}
+
+impl std::error::Error for CounterReset {}
+
+#[derive(
+    Debug,
+)]
+
+/// Probes the rate of some [`Counters`] since
+/// the previous probe. The first probe takes
+/// two samples, [`Probe::PROBE_DELAY`] apart,
+/// and so does a probe after a reset, starting
+/// over from the newest sample.
+///
+/// Every probe moves the baseline, so a
+/// single instance should only be probed
+/// from one place, e.g. a [`Hub`]. Use
+/// [`RateProbe::measure`] for anything
+/// else.
+///
+/// [`Hub`]: crate::hub::Hub
+pub struct RateProbe<P: Counters> {
+    last: Mutex<Option<(Instant, P::Output)>>,
+    pub counters: P,
+}
+
+impl<P: Counters + Default> Default for RateProbe<P> {
+    fn default() -> Self {
+        Self::new(P::default())
+    }
+}
+
+impl<P: Counters> RateProbe<P> {
+    pub fn new(counters: P) -> Self {
+        Self {
+            last: Mutex::new(None),
+            counters,
+        }
+    }
+
+    fn swap(&self, at: Instant, sample: P::Output) -> Option<(Instant, P::Output)> {
+        self.last.lock()
+            .unwrap_or_else(|x| x.into_inner())
+            .replace((at, sample))
+    }
+}
+
+impl<P> RateProbe<P>
+where
+    P: Counters + Sync,
+    P::Error: From<CounterReset>,
+{
+    /// The rate over two samples of its own,
+    /// [`Probe::PROBE_DELAY`] apart, leaving
+    /// the baseline of [`Probe::probe`] alone
+    pub async fn measure(&self) -> Result<P::Rate, P::Error> {
+        let prev = self.counters.probe().await?;
+        let at = Instant::now();
+
+        tokio::time::sleep(Duration::from_millis(
+            P::PROBE_DELAY
+        ))
+        .await;
+
+        let now = self.counters.probe().await?;
+
+        P::rate_since(&now, &prev, at.elapsed())
+            .ok_or(CounterReset.into())
+    }
+}
+
+impl<P> Probe for RateProbe<P>
+where
+    P: Counters + Sync,
+    P::Output: Clone + Send,
+    P::Error: From<CounterReset>,
+{
+    type Output = P::Rate;
+
+    type Error = P::Error;
+
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        let now = self.counters.probe().await?;
+        let at = Instant::now();
+
+        if let Some((then, prev)) = self.swap(at, now.clone()) {
+            if let Some(x) = P::rate_since(&now, &prev, at - then) {
+                return Ok(x);
+            }
+        }
+
+        tokio::time::sleep(Duration::from_millis(
+            P::PROBE_DELAY
+        ))
+        .await;
+
+        let next = self.counters.probe().await?;
+        let next_at = Instant::now();
+
+        self.swap(next_at, next.clone());
+
+        P::rate_since(&next, &now, next_at - at)
+            .ok_or(CounterReset.into())
+    }
+}
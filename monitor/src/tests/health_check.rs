@@ -0,0 +1,146 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::time::timeout;
+use tokio_stream::StreamExt;
+
+use crate::health_check::{Health, Healthcheck};
+
+use super::Any;
+
+/// A check that's as healthy as its flag
+#[derive(
+    Default,
+    Clone,
+)]
+
+struct Flag(Arc<AtomicBool>);
+
+impl Flag {
+    fn set(&self, healthy: bool) {
+        self.0.store(healthy, Ordering::Relaxed);
+    }
+
+    /// Flips the flag after a delay, from
+    /// another task on the same runtime
+    fn set_after(&self, healthy: bool, ms: u64) {
+        let flag = self.clone();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+
+            flag.set(healthy);
+        });
+    }
+}
+
+impl Healthcheck for Flag {
+    async fn health(&self) -> Health {
+        match self.0.load(Ordering::Relaxed) {
+            true => Health::Healthy,
+            false => Health::Unhealthy {
+                reason: Some("flag is down".to_string()),
+            },
+        }
+    }
+
+    const CHECK_DELAY: u64 = 5;
+}
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[tokio::test]
+pub async fn test_health_on_unhealthy_yields() -> Any {
+    let flag = Flag::default();
+
+    flag.set(true);
+
+    // the test runtime has a single thread, so
+    // the flag is only ever flipped if waiting
+    // on the health gives the runtime back
+    flag.set_after(false, 20);
+
+    let mut called = false;
+
+    timeout(TIMEOUT, flag.on_unhealthy(|| called = true)).await?;
+
+    assert!(called);
+
+    timeout(TIMEOUT, flag.on_unhealthy(|| ())).await?;
+
+    flag.set_after(true, 20);
+
+    timeout(TIMEOUT, flag.on_healthy(|| ())).await?;
+
+    assert!(flag.health().await.is_healthy());
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_health_transitions() -> Any {
+    let flag = Flag::default();
+
+    flag.set(true);
+
+    let mut transitions = Box::pin(flag.transitions());
+
+    assert_eq!(timeout(TIMEOUT, transitions.next()).await?, Some(Health::Healthy));
+
+    flag.set_after(false, 20);
+
+    assert_eq!(
+        timeout(TIMEOUT, transitions.next()).await?,
+        Some(Health::Unhealthy {
+            reason: Some("flag is down".to_string()),
+        }),
+    );
+
+    flag.set_after(true, 20);
+
+    assert_eq!(timeout(TIMEOUT, transitions.next()).await?, Some(Health::Healthy));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_health_watch() -> Any {
+    let flag = Flag::default();
+
+    let watch = flag.clone().watch().await;
+
+    assert_eq!(watch.health(), Health::Unhealthy {
+        reason: Some("flag is down".to_string()),
+    });
+
+    let mut transitions = Box::pin(watch.transitions());
+    let mut rx = watch.subscribe();
+
+    assert_eq!(
+        timeout(TIMEOUT, transitions.next()).await?,
+        Some(Health::Unhealthy {
+            reason: Some("flag is down".to_string()),
+        }),
+    );
+
+    flag.set_after(true, 20);
+
+    timeout(TIMEOUT, rx.changed()).await??;
+
+    assert!(rx.borrow_and_update().is_healthy());
+    assert_eq!(timeout(TIMEOUT, transitions.next()).await?, Some(Health::Healthy));
+
+    watch.cancel();
+
+    // cancelling ends every subscriber
+    assert_eq!(timeout(TIMEOUT, transitions.next()).await?, None);
+    assert!(timeout(TIMEOUT, rx.changed()).await?.is_err());
+    assert!(watch.is_cancelled());
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use std::{
+    convert::Infallible,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::time::timeout;
+
+use crate::{hub::Hub, probe::Probe};
+
+use super::Any;
+
+/// Counts how many times it's probed
+#[derive(
+    Default,
+    Clone,
+)]
+
+struct Counter(Arc<AtomicUsize>);
+
+impl Counter {
+    fn count(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Probe for Counter {
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        Ok(self.0.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    const PROBE_DELAY: u64 = 5;
+
+    type Output = usize;
+
+    type Error = Infallible;
+}
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Long enough for a few rounds of the loop
+const IDLE: Duration = Duration::from_millis(50);
+
+#[tokio::test]
+pub async fn test_hub_starts_on_subscribe() -> Any {
+    let counter = Counter::default();
+    let hub = Hub::new(counter.clone());
+
+    tokio::time::sleep(IDLE).await;
+
+    // nobody's listening yet
+    assert_eq!(counter.count(), 0);
+    assert_eq!(hub.subscribers(), 0);
+
+    let mut rx = hub.subscribe();
+    let mut other = hub.subscribe();
+
+    assert_eq!(hub.subscribers(), 2);
+
+    // both share the same samples
+    let sample = timeout(TIMEOUT, rx.recv()).await??;
+
+    assert_eq!(timeout(TIMEOUT, other.recv()).await??, sample);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_hub_stops_on_last_drop() -> Any {
+    let counter = Counter::default();
+    let hub = Hub::new(counter.clone());
+
+    let mut rx = hub.subscribe();
+    let other = hub.subscribe();
+
+    timeout(TIMEOUT, rx.recv()).await??;
+
+    drop(other);
+
+    // still sampling for the one left
+    let count = counter.count();
+
+    timeout(TIMEOUT, rx.recv()).await??;
+
+    assert!(counter.count() > count);
+
+    drop(rx);
+
+    assert_eq!(hub.subscribers(), 0);
+
+    // the loop notices within a round,
+    // then never probes again
+    tokio::time::sleep(IDLE).await;
+
+    let count = counter.count();
+
+    tokio::time::sleep(IDLE).await;
+
+    assert_eq!(counter.count(), count);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_hub_restarts_on_resubscribe() -> Any {
+    let counter = Counter::default();
+    let hub = Hub::new(counter.clone());
+
+    let mut rx = hub.subscribe();
+
+    timeout(TIMEOUT, rx.recv()).await??;
+
+    drop(rx);
+
+    tokio::time::sleep(IDLE).await;
+
+    let count = counter.count();
+
+    let mut rx = hub.subscribe();
+
+    assert_eq!(hub.subscribers(), 1);
+    assert!(timeout(TIMEOUT, rx.recv()).await?? > count);
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+#[cfg(test)]
+mod health_check;
+#[cfg(test)]
+mod hub;
+#[cfg(test)]
+mod rate;
+
+/// What every test returns, so `?`
+/// works on any error
+pub type Any = Result<(), Box<dyn std::error::Error>>;
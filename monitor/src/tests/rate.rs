@@ -0,0 +1,123 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{
+    probe::Probe,
+    rate::{CounterReset, Counters, RateProbe},
+};
+
+use super::Any;
+
+/// A counter that goes up by one every
+/// time it's probed, or down if reversed
+#[derive(
+    Default,
+    Clone,
+)]
+
+struct Ticks {
+    count: Arc<AtomicU64>,
+    reversed: bool,
+}
+
+impl Ticks {
+    fn reversed(count: u64) -> Self {
+        Self {
+            count: Arc::new(AtomicU64::new(count)),
+            reversed: true,
+        }
+    }
+
+    fn get(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    fn set(&self, count: u64) {
+        self.count.store(count, Ordering::Relaxed);
+    }
+}
+
+impl Probe for Ticks {
+    async fn probe(&self) -> Result<Self::Output, Self::Error> {
+        Ok(match self.reversed {
+            true => self.count.fetch_sub(1, Ordering::Relaxed),
+            false => self.count.fetch_add(1, Ordering::Relaxed),
+        })
+    }
+
+    const PROBE_DELAY: u64 = 5;
+
+    type Output = u64;
+
+    type Error = CounterReset;
+}
+
+impl Counters for Ticks {
+    fn rate_since(now: &u64, prev: &u64, _: Duration) -> Option<u64> {
+        now.checked_sub(*prev)
+    }
+
+    type Rate = u64;
+}
+
+#[tokio::test]
+pub async fn test_rate_first_probe() -> Any {
+    let ticks = Ticks::default();
+    let probe = RateProbe::new(ticks.clone());
+
+    // nothing to compare the first
+    // sample against, so it takes two
+    assert_eq!(probe.probe().await?, 1);
+    assert_eq!(ticks.get(), 2);
+
+    assert_eq!(probe.probe().await?, 1);
+    assert_eq!(ticks.get(), 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_rate_reset() -> Any {
+    let ticks = Ticks::default();
+    let probe = RateProbe::new(ticks.clone());
+
+    probe.probe().await?;
+
+    // starts over from the reset counter
+    ticks.set(0);
+
+    assert_eq!(probe.probe().await?, 1);
+    assert_eq!(ticks.get(), 2);
+
+    assert_eq!(probe.probe().await?, 1);
+
+    // resetting every sample is an error
+    let probe = RateProbe::new(Ticks::reversed(100));
+
+    assert_eq!(probe.probe().await, Err(CounterReset));
+    assert_eq!(probe.measure().await, Err(CounterReset));
+
+    Ok(())
+}
+
+#[tokio::test]
+pub async fn test_rate_measure() -> Any {
+    let ticks = Ticks::default();
+    let probe = RateProbe::new(ticks.clone());
+
+    probe.probe().await?;
+
+    assert_eq!(probe.measure().await?, 1);
+    assert_eq!(probe.measure().await?, 1);
+
+    // the baseline is still from before
+    // measuring, 4 samples ago
+    assert_eq!(probe.probe().await?, 5);
+
+    Ok(())
+}